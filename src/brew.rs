@@ -1,13 +1,15 @@
+use std::fmt::Write;
 use std::option::Option::Some;
 
 use handlebars::Handlebars;
 use serde::Serialize;
 use vfs::VfsPath;
 
-use crate::CrateConfig;
 use crate::packaging::Package;
+use crate::workflow::VPath;
+use crate::{CommitOptions, CrateConfig, GITHUB, PushOptions, Vcs};
+use crate::{Error, Result};
 use crate::{new_cargo_config_path, packaging};
-use color_eyre::eyre::Result;
 
 #[derive(Serialize, Default)]
 pub struct Brew {
@@ -17,8 +19,62 @@ pub struct Brew {
     pub homepage: Option<String>,
     pub version: String,
     pub license: String,
-    pub linux: Option<Package>,
+    pub linux: Option<Vec<BrewPackage>>,
     pub macos: Option<Vec<BrewPackage>>,
+    /// Crate's binaries to install. The first one is run by formula's test
+    pub binaries: Vec<BrewBinary>,
+    /// Shell completion scripts found in packages
+    pub completions: Vec<Completion>,
+    /// Man pages found in packages
+    pub man_pages: Vec<ManPage>,
+}
+
+/// Rendered formula or cask together with Homebrew audit rules it violates
+/// (`desc` ones for example). Violations don't prevent its use so they are up to caller to report
+#[derive(Default, Debug)]
+pub struct Rendered {
+    pub content: String,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct BrewBinary {
+    pub name: String,
+    /// Path in package. The same in all packages
+    pub path: String,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct Completion {
+    /// One of bash, zsh or fish
+    pub shell: String,
+    /// Path in package
+    pub path: String,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct ManPage {
+    pub section: String,
+    /// Path in package
+    pub path: String,
+}
+
+/// Formula generation settings
+#[derive(Default, Debug, Clone, Copy)]
+pub struct BrewOptions<'a> {
+    /// Base URI of downloaded packages
+    pub base_uri: &'a str,
+    /// Handlebars formula template content. Template set in crate's manifest
+    /// or the built-in one used if not set
+    pub template: Option<&'a str>,
+    /// Handlebars cask template content. Cask template set in crate's manifest
+    /// or the built-in one used if not set
+    pub cask_template: Option<&'a str>,
+    /// Name to derive formula class name from. Crate's name used if not set
+    pub formula_name: Option<&'a str>,
+    /// Identifier of package installed by cask's pkg installer (used to uninstall it).
+    /// Identifier set in crate's manifest used if not set
+    pub pkg_id: Option<&'a str>,
 }
 
 #[derive(Serialize, Default, Debug)]
@@ -27,7 +83,17 @@ pub struct BrewPackage {
     pub cpu: String,
 }
 
-const TEMPLATE: &str = r#"# typed: false
+const MAX_DESC_LENGTH: usize = 80;
+const TAP_FORMULA_DIR: &str = "Formula";
+const TAP_CASK_DIR: &str = "Casks";
+const CASK_EXTENSIONS: [&str; 3] = ["dmg", "pkg", "zip"];
+const PKG_INSTALLER_EXTENSION: &str = ".pkg";
+const FORMULA: &str = "Formula";
+const CASK: &str = "Cask";
+/// Tap commit message used if it isn't set
+pub const DEFAULT_TAP_MESSAGE: &str = "{name} {version}";
+
+const TEMPLATE: &str = r##"# typed: false
 # frozen_string_literal: true
 # This file was generated by releaser. DO NOT EDIT.
 class {{ formula }} < Formula
@@ -53,90 +119,597 @@ class {{ formula }} < Formula
   {{#if linux }}
 {{lines 1}}
   on_linux do
-    if Hardware::CPU.intel?
-    {{#with linux}}
+    {{#each linux}}
+    if Hardware::CPU.{{ cpu }}?
+    {{#with package}}
       url "{{ url }}"
       sha256 "{{ hash }}"
     {{/with}}
     end
+    {{/each}}
   end
   {{/if}}
 {{#if (or linux macos)}}
 {{lines 1}}
   def install
-    bin.install Dir["*"].select { |f| File.file?(f) && File.executable?(f) }
+    {{#each binaries}}
+    bin.install "{{ path }}"
+    {{/each}}
+    {{#each completions}}
+    {{ shell }}_completion.install "{{ path }}"
+    {{/each}}
+    {{#each man_pages}}
+    man{{ section }}.install "{{ path }}"
+    {{/each}}
+  end
+  {{#each binaries}}
+  {{#if @first}}
+{{lines 1}}
+  test do
+    assert_match version.to_s, shell_output("#{bin}/{{ name }} --version")
   end
+  {{/if}}
+  {{/each}}
 {{lines 1}}
 {{/if}}
 end
-"#;
+"##;
 
 impl Brew {
-    /// Creates Brew struct instance and serializes it into String
+    /// Creates Brew struct instance and serializes it into String.
+    /// Empty if there are no packages
     pub fn serialize(
         crate_path: &VfsPath,
         linux_path: &VfsPath,
+        linux_arm_path: &VfsPath,
         macos_path: &VfsPath,
         macos_arm_path: &VfsPath,
-        base_uri: &str,
-    ) -> Result<String> {
+        options: BrewOptions,
+    ) -> Result<Rendered> {
         let crate_conf = new_cargo_config_path(crate_path)?;
         let config = CrateConfig::open(&crate_conf)?;
 
-        let name = config.package.name;
+        let custom_template = custom_template(
+            crate_path,
+            options.template,
+            config
+                .package
+                .brew_config()
+                .and_then(|c| c.template.as_deref()),
+        )?;
+
+        let archives = [linux_path, linux_arm_path, macos_path, macos_arm_path]
+            .iter()
+            .filter_map(|p| packaging::archive_files(p).ok())
+            .collect::<Vec<Vec<String>>>();
+        let files = merge_files(&archives);
+        let binaries = config
+            .binaries()
+            .into_iter()
+            .map(|name| new_binary(&archives, name))
+            .collect::<Result<Vec<BrewBinary>>>()?;
+        let completions = files
+            .iter()
+            .filter_map(|f| {
+                Some(Completion {
+                    shell: completion_shell(f)?.to_string(),
+                    path: f.clone(),
+                })
+            })
+            .collect();
+        let man_pages = files
+            .iter()
+            .filter_map(|f| {
+                Some(ManPage {
+                    section: man_section(f)?.to_string(),
+                    path: f.clone(),
+                })
+            })
+            .collect();
 
-        let mut macos = vec![];
-        if let Ok(p) = packaging::new_binary_pkg(macos_path, base_uri) {
-            macos.push(BrewPackage {
-                package: p,
-                cpu: "intel".to_string(),
-            });
-        }
-        if let Ok(p) = packaging::new_binary_pkg(macos_arm_path, base_uri) {
-            macos.push(BrewPackage {
-                package: p,
-                cpu: "arm".to_string(),
-            });
-        }
-        let macos = if macos.is_empty() { None } else { Some(macos) };
+        let name = config.package.name;
+        let description = config.package.description.unwrap_or_default();
+        let warnings = desc_warnings(FORMULA, &name, &description);
 
         let brew = Brew {
-            formula: uppercase_first_letter(&name),
+            formula: formula_class(options.formula_name.unwrap_or(&name))?,
             name,
-            description: config.package.description.unwrap_or_default(),
+            description,
             homepage: config.package.homepage,
             version: config.package.version,
             license: config.package.license.unwrap_or_default(),
-            linux: packaging::new_binary_pkg(linux_path, base_uri).ok(),
-            macos,
+            linux: new_cpu_packages(linux_path, linux_arm_path, options.base_uri),
+            macos: new_cpu_packages(macos_path, macos_arm_path, options.base_uri),
+            binaries,
+            completions,
+            man_pages,
         };
 
         if brew.linux.is_none() && brew.macos.is_none() {
-            Ok(String::new())
+            Ok(Rendered::default())
+        } else {
+            let formula = serialize_brew(&brew, custom_template.as_deref().unwrap_or(TEMPLATE))?;
+            validate_class(&formula, &brew.formula)?;
+            Ok(Rendered {
+                content: formula,
+                warnings,
+            })
+        }
+    }
+}
+
+/// Homebrew Cask of macOS application distributed as dmg, pkg or zip
+#[derive(Serialize, Default)]
+pub struct Cask {
+    pub token: String,
+    pub name: String,
+    pub description: String,
+    pub homepage: Option<String>,
+    pub version: String,
+    pub packages: Vec<BrewPackage>,
+    /// App bundles moved into Applications directory
+    pub apps: Vec<String>,
+    /// Installer file name if cask is distributed as pkg
+    pub pkg: Option<String>,
+    /// Identifier of package installed by pkg installer. Set if there is pkg installer
+    pub pkg_id: Option<String>,
+    /// Binaries linked into Homebrew's bin directory. Crate's binaries
+    /// used if cask has neither apps nor pkg installer
+    pub binaries: Vec<String>,
+    /// GitHub repository which releases are checked for new versions
+    pub livecheck: Option<String>,
+}
+
+const CASK_TEMPLATE: &str = r#"# typed: false
+# frozen_string_literal: true
+# This file was generated by releaser. DO NOT EDIT.
+cask "{{ token }}" do
+  version "{{ version }}"
+{{#if packages.[1]}}
+{{#each packages}}
+
+  on_{{ cpu }} do
+  {{#with package}}
+    sha256 "{{ hash }}"
+    url "{{ url }}"
+  {{/with}}
+  end
+{{/each}}
+{{else}}
+{{#with packages.[0].package}}
+
+  sha256 "{{ hash }}"
+  url "{{ url }}"
+{{/with}}
+{{/if}}
+
+  name "{{ name }}"
+  desc "{{ description }}"
+{{#if homepage}}
+  homepage "{{ homepage }}"
+{{/if}}
+{{#if livecheck}}
+
+  livecheck do
+    url "{{ livecheck }}"
+    strategy :github_latest
+  end
+{{/if}}
+
+{{#each apps}}
+  app "{{ this }}"
+{{/each}}
+{{#if pkg}}
+  pkg "{{ pkg }}"
+{{/if}}
+{{#each binaries}}
+  binary "{{ this }}"
+{{/each}}
+{{#if pkg_id}}
+
+  uninstall pkgutil: "{{ pkg_id }}"
+{{/if}}
+end
+"#;
+
+impl Cask {
+    /// Creates Cask struct instance from macOS dmg, pkg or zip packages and serializes it into String
+    /// (empty if there are no packages).
+    /// Formula name set in options used as cask token. Single package is used on both architectures
+    pub fn serialize(
+        crate_path: &VfsPath,
+        macos_path: &VfsPath,
+        macos_arm_path: &VfsPath,
+        apps: &[String],
+        options: BrewOptions,
+    ) -> Result<Rendered> {
+        let crate_conf = new_cargo_config_path(crate_path)?;
+        let config = CrateConfig::open(&crate_conf)?;
+
+        let packages = [(macos_arm_path, "arm"), (macos_path, "intel")]
+            .into_iter()
+            .filter_map(|(path, cpu)| {
+                let package = packaging::new_pkg(path, options.base_uri, &CASK_EXTENSIONS).ok()?;
+                Some(BrewPackage {
+                    package,
+                    cpu: cpu.to_string(),
+                })
+            })
+            .collect::<Vec<BrewPackage>>();
+        if packages.is_empty() {
+            return Ok(Rendered::default());
+        }
+
+        let custom_template = custom_template(
+            crate_path,
+            options.cask_template,
+            config
+                .package
+                .brew_config()
+                .and_then(|c| c.cask_template.as_deref()),
+        )?;
+        let pkg = pkg_installer(&packages)?;
+        let pkg_id = match (&pkg, options.pkg_id) {
+            (None, _) => None,
+            (Some(_), Some(id)) => Some(id.to_string()),
+            (Some(_), None) => Some(
+                config
+                    .package
+                    .brew_config()
+                    .and_then(|c| c.pkg_id.clone())
+                    .ok_or_else(|| {
+                        Error::packaging(
+                            "Cask pkg installer must have package identifier to uninstall it. Set it by --pkgid option or pkg-id setting in crate's manifest",
+                        )
+                    })?,
+            ),
+        };
+        let binaries = if apps.is_empty() && pkg.is_none() {
+            config.binaries()
+        } else {
+            vec![]
+        };
+
+        let name = config.package.name;
+        let description = config.package.description.unwrap_or_default();
+        let warnings = desc_warnings(CASK, &name, &description);
+
+        let cask = Cask {
+            token: cask_token(options.formula_name.unwrap_or(&name)),
+            name,
+            description,
+            homepage: config.package.homepage,
+            version: config.package.version,
+            packages,
+            apps: apps.to_vec(),
+            pkg,
+            pkg_id,
+            binaries,
+            livecheck: config.package.repository.filter(|r| r.starts_with(GITHUB)),
+        };
+        Ok(Rendered {
+            content: serialize_brew(&cask, custom_template.as_deref().unwrap_or(CASK_TEMPLATE))?,
+            warnings,
+        })
+    }
+}
+
+/// Template content to render instead of the built-in one. Template set in command line
+/// overrides one set in crate's manifest (path relative to crate's root)
+fn custom_template(
+    crate_path: &VfsPath,
+    template: Option<&str>,
+    manifest_template: Option<&str>,
+) -> Result<Option<String>> {
+    match (template, manifest_template) {
+        (Some(t), _) => Ok(Some(t.to_string())),
+        (None, Some(path)) => Ok(Some(crate_path.join(path)?.read_to_string()?)),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Installer file name if packages are pkg installers. All architectures
+/// must use the same file name because cask has single pkg stanza
+fn pkg_installer(packages: &[BrewPackage]) -> Result<Option<String>> {
+    let mut names = packages
+        .iter()
+        .filter_map(|p| p.package.url.rsplit('/').next())
+        .filter(|f| f.ends_with(PKG_INSTALLER_EXTENSION));
+    let Some(first) = names.next() else {
+        return Ok(None);
+    };
+    if names.all(|n| n == first) {
+        Ok(Some(first.to_string()))
+    } else {
+        Err(Error::packaging(
+            "Cask pkg installers must have the same file name for all architectures",
+        ))
+    }
+}
+
+/// Cask token derived from name the way Homebrew does it:
+/// lowercased with spaces and underscores replaced by hyphens
+fn cask_token(name: &str) -> String {
+    name.to_lowercase().replace([' ', '_'], "-")
+}
+
+/// Homebrew tap repository update settings
+#[derive(Default, Debug)]
+pub struct TapOptions<'a> {
+    /// Formula name. Crate's name used if not set
+    pub formula_name: Option<&'a str>,
+    /// Commit message. `{name}` and `{version}` are replaced by formula name and crate's version.
+    /// [`DEFAULT_TAP_MESSAGE`] used if not set
+    pub message: Option<&'a str>,
+    /// Whether to sign commit (GPG or SSH key configured in git)
+    pub sign: bool,
+    /// Whether cask published instead of formula
+    pub cask: bool,
+    pub push: PushOptions<'a>,
+}
+
+/// Homebrew tap repository formulas published into
+pub struct Tap<V: Vcs> {
+    vcs: V,
+}
+
+impl<V: Vcs> Tap<V> {
+    pub fn new(vcs: V) -> Self {
+        Self { vcs }
+    }
+
+    /// Writes formula into tap's `Formula` directory (cask into `Casks` directory), commits
+    /// and pushes it. Returns false if formula hasn't changed so there was nothing to commit
+    pub fn update(
+        &self,
+        tap: &VPath<'_>,
+        crate_path: &VfsPath,
+        formula: &str,
+        options: TapOptions,
+    ) -> Result<bool> {
+        let config = CrateConfig::open(&new_cargo_config_path(crate_path)?)?;
+        let name = options.formula_name.unwrap_or(&config.package.name);
+        let (dir, name) = if options.cask {
+            (TAP_CASK_DIR, cask_token(name))
         } else {
-            serialize_brew(&brew)
+            (TAP_FORMULA_DIR, name.to_string())
+        };
+        let file = format!("{dir}/{name}.rb");
+
+        tap.virtual_path.join(dir)?.create_dir_all()?;
+        let path = tap.virtual_path.join(&file)?;
+        if path.exists()? && path.read_to_string()? == formula {
+            return Ok(false);
+        }
+        path.create_file()?.write_all(formula.as_bytes())?;
+
+        let message = options
+            .message
+            .unwrap_or(DEFAULT_TAP_MESSAGE)
+            .replace("{name}", &name)
+            .replace("{version}", &config.package.version);
+        let commit_options = CommitOptions {
+            message: &message,
+            files: &[file],
+            sign: options.sign,
+        };
+        self.vcs.commit(tap.real_path, commit_options)?;
+        self.vcs.push(tap.real_path, options.push)?;
+        Ok(true)
+    }
+}
+
+/// Creates packages of Intel and ARM CPUs. None if there are no packages at all
+fn new_cpu_packages(
+    intel_path: &VfsPath,
+    arm_path: &VfsPath,
+    base_uri: &str,
+) -> Option<Vec<BrewPackage>> {
+    let packages = [(intel_path, "intel"), (arm_path, "arm")]
+        .into_iter()
+        .filter_map(|(path, cpu)| {
+            let package = packaging::new_binary_pkg(path, base_uri).ok()?;
+            Some(BrewPackage {
+                package,
+                cpu: cpu.to_string(),
+            })
+        })
+        .collect::<Vec<BrewPackage>>();
+    if packages.is_empty() {
+        None
+    } else {
+        Some(packages)
+    }
+}
+
+/// Files of all packages without duplicates
+fn merge_files(archives: &[Vec<String>]) -> Vec<String> {
+    let mut files = vec![];
+    for file in archives.iter().flatten() {
+        if !files.contains(file) {
+            files.push(file.clone());
         }
     }
+    files
+}
+
+/// Binary with its path in packages. Binary may be in package's subdirectory
+/// but formula has single install block so the path must be the same in all packages
+fn new_binary(archives: &[Vec<String>], name: String) -> Result<BrewBinary> {
+    let mut paths = archives.iter().map(|files| {
+        files
+            .iter()
+            .find(|f| *f == &name || f.ends_with(&format!("/{name}")))
+            .cloned()
+            .unwrap_or_else(|| name.clone())
+    });
+    let path = paths.next().unwrap_or_else(|| name.clone());
+    if let Some(other) = paths.find(|p| *p != path) {
+        return Err(Error::packaging(format!(
+            "Binary {name} has different paths in packages ({path} and {other}) but formula installs it from the same path on all platforms"
+        )));
+    }
+    Ok(BrewBinary { name, path })
+}
+
+/// Shell of completion script using common naming conventions:
+/// `*.bash`, `*.fish`, `*.zsh` or `_name` for zsh
+fn completion_shell(path: &str) -> Option<&'static str> {
+    let file = path.rsplit('/').next()?;
+    if file.ends_with(".bash") {
+        Some("bash")
+    } else if file.ends_with(".fish") {
+        Some("fish")
+    } else if file.ends_with(".zsh") || (file.starts_with('_') && !file.contains('.')) {
+        Some("zsh")
+    } else {
+        None
+    }
+}
+
+/// Section of man page i.e. N of `name.N` or `name.N.gz` file
+fn man_section(path: &str) -> Option<char> {
+    let file = path.rsplit('/').next()?;
+    let file = file.strip_suffix(".gz").unwrap_or(file);
+    let (name, section) = file.rsplit_once('.')?;
+    let mut chars = section.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c @ '1'..='8'), None) if !name.is_empty() => Some(c),
+        _ => None,
+    }
 }
 
-fn uppercase_first_letter(s: &str) -> String {
-    let mut chars_it = s.chars();
-    if let Some(f) = chars_it.next() {
-        f.to_uppercase().chain(chars_it).collect()
+/// Derives formula class name from formula name the way Homebrew does:
+/// capitalizes name, removes `-`, `_`, `.` and spaces capitalizing letter after them,
+/// replaces `+` with `x` and `@` before version with `AT`
+fn formula_class(name: &str) -> Result<String> {
+    let mut class = String::new();
+    let mut chars = name.chars().peekable();
+    if let Some(first) = chars.next() {
+        class.extend(first.to_uppercase());
+    }
+    while let Some(c) = chars.next() {
+        match c {
+            '-' | '_' | '.' | ' ' if chars.peek().is_some_and(char::is_ascii_alphanumeric) => {
+                class.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+            }
+            '+' => class.push('x'),
+            _ => class.extend(c.to_lowercase()),
+        }
+    }
+    if let Some(at) = class.find('@')
+        && at > 0
+        && class[at + 1..].starts_with(|c: char| c.is_ascii_digit())
+    {
+        class.replace_range(at..=at, "AT");
+    }
+    if is_ruby_constant(&class) {
+        Ok(class)
     } else {
-        String::new()
+        Err(Error::packaging(format!(
+            "Formula class name {class} derived from {name} isn't valid Ruby class name"
+        )))
+    }
+}
+
+fn is_ruby_constant(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Checks that rendered formula (custom template may be used) defines formula class expected
+fn validate_class(formula: &str, class: &str) -> Result<()> {
+    let defined = formula.lines().find_map(|l| {
+        l.trim()
+            .strip_prefix("class ")?
+            .strip_suffix("< Formula")
+            .map(str::trim)
+    });
+    match defined {
+        Some(c) if c == class => Ok(()),
+        Some(c) => Err(Error::packaging(format!(
+            "Formula defines class {c} but {class} expected"
+        ))),
+        None => Err(Error::packaging(format!(
+            "Formula doesn't define class {class} < Formula"
+        ))),
+    }
+}
+
+/// Homebrew's `desc` audit rules violated by description of formula or cask (kind). Empty if there are none
+pub fn desc_warnings(kind: &str, name: &str, description: &str) -> Vec<String> {
+    let mut warnings = vec![];
+    if description.is_empty() {
+        warnings.push(format!("{kind} should have a desc"));
+        return warnings;
+    }
+    let lower = description.to_lowercase();
+    let first_word = lower.split_whitespace().next().unwrap_or_default();
+    if ["a", "an", "the"].contains(&first_word) {
+        warnings.push("Description shouldn't start with an article".to_string());
+    }
+    if first_word == name.to_lowercase() {
+        warnings.push(format!(
+            "Description shouldn't start with the {} name",
+            kind.to_lowercase()
+        ));
+    }
+    if description.starts_with(char::is_lowercase) {
+        warnings.push("Description should start with a capital letter".to_string());
+    }
+    if description.ends_with('.') {
+        warnings.push("Description shouldn't end with a full stop".to_string());
+    }
+    let length = description.chars().count();
+    if length > MAX_DESC_LENGTH {
+        warnings.push(format!(
+            "Description is too long. It should be less than {MAX_DESC_LENGTH} characters but it is {length}"
+        ));
+    }
+    warnings
+}
+
+/// Escapes string to be placed into Ruby double-quoted string literal
+fn ruby_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // interpolation sequences
+            '#' if matches!(chars.peek(), Some('{' | '$' | '@')) => escaped.push_str("\\#"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{{{:x}}}", u32::from(c));
+            }
+            c => escaped.push(c),
+        }
     }
+    escaped
 }
 
-fn serialize_brew<T: Serialize>(data: &T) -> Result<String> {
+/// Renders formula template. Besides built-in handlebars helpers `upper`, `lower`
+/// and `replace` string helpers are available to templates. Values are escaped
+/// to be safely placed into Ruby double-quoted strings
+fn serialize_brew<T: Serialize>(data: &T, template: &str) -> Result<String> {
     handlebars_helper!(lines: |count: i32| {
         for _ in 0..count {
           println!();
         };
     });
+    handlebars_helper!(upper: |s: str| s.to_uppercase());
+    handlebars_helper!(lower: |s: str| s.to_lowercase());
+    handlebars_helper!(replace: |s: str, from: str, to: str| s.replace(from, to));
     let mut reg = Handlebars::new();
+    reg.register_escape_fn(ruby_escape);
     reg.register_helper("lines", Box::new(lines));
-    Ok(reg.render_template(TEMPLATE, data)?)
+    reg.register_helper("upper", Box::new(upper));
+    reg.register_helper("lower", Box::new(lower));
+    reg.register_helper("replace", Box::new(replace));
+    Ok(reg.render_template(template, data)?)
 }
 
 #[cfg(test)]
@@ -144,41 +717,132 @@ mod tests {
     #![allow(clippy::unwrap_in_result)]
     #![allow(clippy::unwrap_used)]
     use super::*;
-    use crate::CARGO_CONFIG;
+    use crate::{CARGO_CONFIG, MockVcs};
     use rstest::{fixture, rstest};
     use vfs::MemoryFS;
 
-    #[test]
-    fn uppercase_first_letter_test() {
+    #[rstest]
+    #[case::lowercase("test", "Test")]
+    #[case::uppercase("Test", "Test")]
+    #[case::hyphen("my-tool", "MyTool")]
+    #[case::underscore("foo_bar", "FooBar")]
+    #[case::dot("foo.bar", "FooBar")]
+    #[case::mixed_case("myTool", "Mytool")]
+    #[case::plus("foo++", "Fooxx")]
+    #[case::versioned("python@3.12", "PythonAT312")]
+    #[case::digits("lib2-3", "Lib23")]
+    #[trace]
+    fn formula_class_tests(#[case] name: &str, #[case] expected: &str) {
         // Arrange
 
         // Act
-        let r = uppercase_first_letter("test");
+        let actual = formula_class(name).unwrap();
 
         // Assert
-        assert_eq!(r, "Test");
+        assert_eq!(expected, actual);
     }
 
-    #[test]
-    fn uppercase_first_letter_test_already_uppercased() {
+    #[rstest]
+    #[case::empty("")]
+    #[case::digit_first("2fa")]
+    #[case::at_without_version("foo@bar")]
+    #[trace]
+    fn formula_class_invalid_tests(#[case] name: &str) {
+        // Arrange
+
+        // Act
+        let actual = formula_class(name);
+
+        // Assert
+        assert!(actual.is_err());
+    }
+
+    #[rstest]
+    #[case::valid("# x\nclass Solv < Formula\nend\n", true)]
+    #[case::other_class("class Solp < Formula\nend\n", false)]
+    #[case::no_class("cask \"solv\" do\nend\n", false)]
+    #[trace]
+    fn validate_class_tests(#[case] formula: &str, #[case] expected: bool) {
+        // Arrange
+
+        // Act
+        let actual = validate_class(formula, "Solv");
+
+        // Assert
+        assert_eq!(expected, actual.is_ok());
+    }
+
+    #[rstest]
+    #[case::plain("Solution validator", "Solution validator")]
+    #[case::quote("Say \"hi\"", "Say \\\"hi\\\"")]
+    #[case::backslash("C:\\tools", "C:\\\\tools")]
+    #[case::interpolation("#{system('ls')}", "\\#{system('ls')}")]
+    #[case::global_var("#$0 #@x", "\\#$0 \\#@x")]
+    #[case::hash("https://x.io/#readme", "https://x.io/#readme")]
+    #[case::ampersand("Tom & Jerry <3>", "Tom & Jerry <3>")]
+    #[case::newline("a\nb\tc", "a\\nb\\tc")]
+    #[case::control("a\u{1}", "a\\u{1}")]
+    #[trace]
+    fn ruby_escape_tests(#[case] s: &str, #[case] expected: &str) {
+        // Arrange
+
+        // Act
+        let actual = ruby_escape(s);
+
+        // Assert
+        assert_eq!(expected, actual);
+    }
+
+    #[rstest]
+    #[case::valid("Microsoft Visual Studio solution validator", &[])]
+    #[case::empty("", &["Formula should have a desc"])]
+    #[case::article("A solution validator", &["Description shouldn't start with an article"])]
+    #[case::name("Solv validates solutions", &["Description shouldn't start with the formula name"])]
+    #[case::lowercase("solution validator", &["Description should start with a capital letter"])]
+    #[case::full_stop("Solution validator.", &["Description shouldn't end with a full stop"])]
+    #[case::too_long(
+        "Validates Microsoft Visual Studio solutions and projects and reports all problems found",
+        &["Description is too long. It should be less than 80 characters but it is 87"]
+    )]
+    #[case::several(
+        "the solution validator.",
+        &[
+            "Description shouldn't start with an article",
+            "Description should start with a capital letter",
+            "Description shouldn't end with a full stop"
+        ]
+    )]
+    #[trace]
+    fn desc_warnings_tests(#[case] description: &str, #[case] expected: &[&str]) {
         // Arrange
 
         // Act
-        let r = uppercase_first_letter("Test");
+        let actual = desc_warnings(FORMULA, "solv", description);
 
         // Assert
-        assert_eq!(r, "Test");
+        assert_eq!(expected, actual);
     }
 
     #[test]
-    fn uppercase_first_letter_test_empty_string() {
+    fn serialize_brew_escaping_test() {
         // Arrange
+        let brew = Brew {
+            formula: "Solv".to_string(),
+            name: "solv".to_string(),
+            description: "Validates \"solutions\" & #{projects}".to_string(),
+            homepage: Some("https://github.com/aegoroff/solv?a=1&b=2".to_string()),
+            version: "v0.4.0".to_string(),
+            license: "MIT OR Apache-2.0".to_string(),
+            ..Default::default()
+        };
 
         // Act
-        let r = uppercase_first_letter("");
+        let result = serialize_brew(&brew, TEMPLATE).unwrap();
 
         // Assert
-        assert_eq!(r, "");
+        assert!(result.contains("  desc \"Validates \\\"solutions\\\" & \\#{projects}\"\n"));
+        assert!(result.contains("  homepage \"https://github.com/aegoroff/solv?a=1&b=2\"\n"));
+        assert!(result.contains("  license \"MIT OR Apache-2.0\"\n"));
     }
 
     #[test]
@@ -193,10 +857,11 @@ mod tests {
             license: "MIT".to_string(),
             linux: None,
             macos: None,
+            ..Default::default()
         };
 
         // Act
-        let result = serialize_brew(&brew).unwrap_or_default();
+        let result = serialize_brew(&brew, TEMPLATE).unwrap_or_default();
 
         // Assert
         assert_eq!(
@@ -234,14 +899,19 @@ end
             license: "MIT".to_string(),
             linux: None,
             macos: Some(vec![mac_pkg]),
+            binaries: vec![BrewBinary {
+                name: "solv".to_string(),
+                path: "solv".to_string(),
+            }],
+            ..Default::default()
         };
 
         // Act
-        let result = serialize_brew(&brew).unwrap_or_default();
+        let result = serialize_brew(&brew, TEMPLATE).unwrap_or_default();
 
         // Assert
         assert_eq!(
-            r#"# typed: false
+            r##"# typed: false
 # frozen_string_literal: true
 # This file was generated by releaser. DO NOT EDIT.
 class Solv < Formula
@@ -258,11 +928,15 @@ class Solv < Formula
   end
 
   def install
-    bin.install Dir["*"].select { |f| File.file?(f) && File.executable?(f) }
+    bin.install "solv"
+  end
+
+  test do
+    assert_match version.to_s, shell_output("#{bin}/solv --version")
   end
 
 end
-"#,
+"##,
             result
         )
     }
@@ -296,14 +970,19 @@ end
             license: "MIT".to_string(),
             linux: None,
             macos: Some(vec![mac_pkg_intel, mac_pkg_arm]),
+            binaries: vec![BrewBinary {
+                name: "solv".to_string(),
+                path: "solv".to_string(),
+            }],
+            ..Default::default()
         };
 
         // Act
-        let result = serialize_brew(&brew).unwrap_or_default();
+        let result = serialize_brew(&brew, TEMPLATE).unwrap_or_default();
 
         // Assert
         assert_eq!(
-            r#"# typed: false
+            r##"# typed: false
 # frozen_string_literal: true
 # This file was generated by releaser. DO NOT EDIT.
 class Solv < Formula
@@ -324,11 +1003,15 @@ class Solv < Formula
   end
 
   def install
-    bin.install Dir["*"].select { |f| File.file?(f) && File.executable?(f) }
+    bin.install "solv"
+  end
+
+  test do
+    assert_match version.to_s, shell_output("#{bin}/solv --version")
   end
 
 end
-"#,
+"##,
             result
         )
     }
@@ -347,16 +1030,24 @@ end
             homepage: None,
             version: "v0.4.0".to_string(),
             license: "MIT".to_string(),
-            linux: Some(linux),
+            linux: Some(vec![BrewPackage {
+                package: linux,
+                cpu: "intel".to_string(),
+            }]),
             macos: None,
+            binaries: vec![BrewBinary {
+                name: "solv".to_string(),
+                path: "solv".to_string(),
+            }],
+            ..Default::default()
         };
 
         // Act
-        let result = serialize_brew(&brew).unwrap_or_default();
+        let result = serialize_brew(&brew, TEMPLATE).unwrap_or_default();
 
         // Assert
         assert_eq!(
-            r#"# typed: false
+            r##"# typed: false
 # frozen_string_literal: true
 # This file was generated by releaser. DO NOT EDIT.
 class Solv < Formula
@@ -373,29 +1064,29 @@ class Solv < Formula
   end
 
   def install
-    bin.install Dir["*"].select { |f| File.file?(f) && File.executable?(f) }
+    bin.install "solv"
+  end
+
+  test do
+    assert_match version.to_s, shell_output("#{bin}/solv --version")
   end
 
 end
-"#,
+"##,
             result
         )
     }
 
     #[test]
-    fn serialize_brew_all_test() {
+    fn serialize_brew_linux_all_test() {
         // Arrange
-        let macos = Package {
-            url: "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_darwin_amd64.tar.gz".to_string(),
-            hash: "9a6c8144ed77cd5e2b88031109ac4285ca08e8c644f3d022a389359470721a7b".to_string(),
-        };
-        let linux = Package {
+        let linux_intel = Package {
             url: "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_linux_amd64.tar.gz".to_string(),
             hash: "fb5c2f5d41c7d3485898de9905736dc8c540a912dc95d3a55bd9360901689811".to_string(),
         };
-        let mac_pkg = BrewPackage {
-            package: macos,
-            cpu: "intel".to_string(),
+        let linux_arm = Package {
+            url: "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_linux_arm64.tar.gz".to_string(),
+            hash: "9a6c8144ed77cd5e2b88031109ac4285ca08e8c644f3d022a389359470721a7b".to_string(),
         };
         let brew = Brew {
             formula: "Solv".to_string(),
@@ -404,16 +1095,30 @@ end
             homepage: None,
             version: "v0.4.0".to_string(),
             license: "MIT".to_string(),
-            linux: Some(linux),
-            macos: Some(vec![mac_pkg]),
+            linux: Some(vec![
+                BrewPackage {
+                    package: linux_intel,
+                    cpu: "intel".to_string(),
+                },
+                BrewPackage {
+                    package: linux_arm,
+                    cpu: "arm".to_string(),
+                },
+            ]),
+            macos: None,
+            binaries: vec![BrewBinary {
+                name: "solv".to_string(),
+                path: "solv".to_string(),
+            }],
+            ..Default::default()
         };
 
         // Act
-        let result = serialize_brew(&brew).unwrap_or_default();
+        let result = serialize_brew(&brew, TEMPLATE).unwrap_or_default();
 
         // Assert
         assert_eq!(
-            r#"# typed: false
+            r##"# typed: false
 # frozen_string_literal: true
 # This file was generated by releaser. DO NOT EDIT.
 class Solv < Formula
@@ -422,13 +1127,84 @@ class Solv < Formula
   version "v0.4.0"
   license "MIT"
 
-  on_macos do
+  on_linux do
     if Hardware::CPU.intel?
-      url "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_darwin_amd64.tar.gz"
+      url "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_linux_amd64.tar.gz"
+      sha256 "fb5c2f5d41c7d3485898de9905736dc8c540a912dc95d3a55bd9360901689811"
+    end
+    if Hardware::CPU.arm?
+      url "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_linux_arm64.tar.gz"
       sha256 "9a6c8144ed77cd5e2b88031109ac4285ca08e8c644f3d022a389359470721a7b"
     end
   end
 
+  def install
+    bin.install "solv"
+  end
+
+  test do
+    assert_match version.to_s, shell_output("#{bin}/solv --version")
+  end
+
+end
+"##,
+            result
+        )
+    }
+
+    #[test]
+    fn serialize_brew_binaries_test() {
+        // Arrange
+        let linux = Package {
+            url: "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_linux_amd64.tar.gz".to_string(),
+            hash: "fb5c2f5d41c7d3485898de9905736dc8c540a912dc95d3a55bd9360901689811".to_string(),
+        };
+        let brew = Brew {
+            formula: "Solv".to_string(),
+            name: "solv".to_string(),
+            description: "desc".to_string(),
+            homepage: None,
+            version: "v0.4.0".to_string(),
+            license: "MIT".to_string(),
+            linux: Some(vec![BrewPackage {
+                package: linux,
+                cpu: "intel".to_string(),
+            }]),
+            macos: None,
+            binaries: vec![
+                BrewBinary {
+                    name: "solv".to_string(),
+                    path: "solv".to_string(),
+                },
+                BrewBinary {
+                    name: "solp".to_string(),
+                    path: "bin/solp".to_string(),
+                },
+            ],
+            completions: vec![Completion {
+                shell: "bash".to_string(),
+                path: "completions/solv.bash".to_string(),
+            }],
+            man_pages: vec![ManPage {
+                section: "1".to_string(),
+                path: "solv.1".to_string(),
+            }],
+        };
+
+        // Act
+        let result = serialize_brew(&brew, TEMPLATE).unwrap_or_default();
+
+        // Assert
+        assert_eq!(
+            r##"# typed: false
+# frozen_string_literal: true
+# This file was generated by releaser. DO NOT EDIT.
+class Solv < Formula
+  desc "desc"
+  homepage ""
+  version "v0.4.0"
+  license "MIT"
+
   on_linux do
     if Hardware::CPU.intel?
       url "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_linux_amd64.tar.gz"
@@ -437,43 +1213,405 @@ class Solv < Formula
   end
 
   def install
-    bin.install Dir["*"].select { |f| File.file?(f) && File.executable?(f) }
+    bin.install "solv"
+    bin.install "bin/solp"
+    bash_completion.install "completions/solv.bash"
+    man1.install "solv.1"
+  end
+
+  test do
+    assert_match version.to_s, shell_output("#{bin}/solv --version")
   end
 
 end
-"#,
+"##,
             result
         )
     }
 
     #[rstest]
-    fn new_brew_all_correct(root: VfsPath) {
+    #[case::bash("completions/solv.bash", Some("bash"))]
+    #[case::fish("solv.fish", Some("fish"))]
+    #[case::zsh("complete/_solv", Some("zsh"))]
+    #[case::zsh_extension("solv.zsh", Some("zsh"))]
+    #[case::powershell("_solv.ps1", None)]
+    #[case::binary("solv", None)]
+    #[trace]
+    fn completion_shell_tests(#[case] path: &str, #[case] expected: Option<&str>) {
         // Arrange
-        let linux_path = root.join("linux").unwrap();
-        let macos_path = root.join("macos").unwrap();
-        let macos_arm_path = root.join("macos_arm").unwrap();
+
+        // Act
+        let actual = completion_shell(path);
+
+        // Assert
+        assert_eq!(expected, actual);
+    }
+
+    #[rstest]
+    #[case::plain("doc/solv.1", Some('1'))]
+    #[case::compressed("solv.8.gz", Some('8'))]
+    #[case::not_section("solv.9", None)]
+    #[case::binary("solv", None)]
+    #[case::archive("solv.tar.gz", None)]
+    #[trace]
+    fn man_section_tests(#[case] path: &str, #[case] expected: Option<char>) {
+        // Arrange
+
+        // Act
+        let actual = man_section(path);
+
+        // Assert
+        assert_eq!(expected, actual);
+    }
+
+    #[rstest]
+    fn new_brew_installs_package_files(root: VfsPath) {
+        // Arrange
+        let linux_path = root.join("linux").unwrap();
+        linux_path
+            .join("linux-solv.tar.gz")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(&crate::packaging::tests::tar_gz(&[
+                "solv-0.1.13/solv",
+                "solv-0.1.13/completions/_solv",
+                "solv-0.1.13/solv.1",
+            ]))
+            .unwrap();
+
+        // Act
+        let result = Brew::serialize(
+            &root,
+            &linux_path,
+            &root.join("linux_arm").unwrap(),
+            &root.join("macos").unwrap(),
+            &root.join("macos_arm").unwrap(),
+            BrewOptions {
+                base_uri: "http://localhost",
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        let r = result.unwrap().content;
+        assert!(r.contains("    bin.install \"solv\"\n"));
+        assert!(r.contains("    zsh_completion.install \"completions/_solv\"\n"));
+        assert!(r.contains("    man1.install \"solv.1\"\n"));
+        assert!(r.contains("shell_output(\"#{bin}/solv --version\")"));
+    }
+
+    #[rstest]
+    fn new_brew_binary_paths_differ(root: VfsPath) {
+        // Arrange
+        let linux_path = root.join("linux").unwrap();
+        linux_path
+            .join("linux-solv.tar.gz")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(&crate::packaging::tests::tar_gz(&["solv", "solv.1"]))
+            .unwrap();
+        let macos_path = root.join("macos").unwrap();
+        macos_path
+            .join("macos-solv.tar.gz")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(&crate::packaging::tests::tar_gz(&["bin/solv", "solv.1"]))
+            .unwrap();
 
         // Act
         let result = Brew::serialize(
             &root,
             &linux_path,
+            &root.join("linux_arm").unwrap(),
+            &macos_path,
+            &root.join("macos_arm").unwrap(),
+            BrewOptions {
+                base_uri: "http://localhost",
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("solv and bin/solv"), "{err}");
+    }
+
+    #[test]
+    fn serialize_brew_all_test() {
+        // Arrange
+        let macos = Package {
+            url: "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_darwin_amd64.tar.gz".to_string(),
+            hash: "9a6c8144ed77cd5e2b88031109ac4285ca08e8c644f3d022a389359470721a7b".to_string(),
+        };
+        let linux = Package {
+            url: "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_linux_amd64.tar.gz".to_string(),
+            hash: "fb5c2f5d41c7d3485898de9905736dc8c540a912dc95d3a55bd9360901689811".to_string(),
+        };
+        let mac_pkg = BrewPackage {
+            package: macos,
+            cpu: "intel".to_string(),
+        };
+        let brew = Brew {
+            formula: "Solv".to_string(),
+            name: "solv".to_string(),
+            description: "desc".to_string(),
+            homepage: None,
+            version: "v0.4.0".to_string(),
+            license: "MIT".to_string(),
+            linux: Some(vec![BrewPackage {
+                package: linux,
+                cpu: "intel".to_string(),
+            }]),
+            macos: Some(vec![mac_pkg]),
+            binaries: vec![BrewBinary {
+                name: "solv".to_string(),
+                path: "solv".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        // Act
+        let result = serialize_brew(&brew, TEMPLATE).unwrap_or_default();
+
+        // Assert
+        assert_eq!(
+            r##"# typed: false
+# frozen_string_literal: true
+# This file was generated by releaser. DO NOT EDIT.
+class Solv < Formula
+  desc "desc"
+  homepage ""
+  version "v0.4.0"
+  license "MIT"
+
+  on_macos do
+    if Hardware::CPU.intel?
+      url "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_darwin_amd64.tar.gz"
+      sha256 "9a6c8144ed77cd5e2b88031109ac4285ca08e8c644f3d022a389359470721a7b"
+    end
+  end
+
+  on_linux do
+    if Hardware::CPU.intel?
+      url "https://github.com/aegoroff/solt/releases/download/v1.0.7/solt_1.0.7_linux_amd64.tar.gz"
+      sha256 "fb5c2f5d41c7d3485898de9905736dc8c540a912dc95d3a55bd9360901689811"
+    end
+  end
+
+  def install
+    bin.install "solv"
+  end
+
+  test do
+    assert_match version.to_s, shell_output("#{bin}/solv --version")
+  end
+
+end
+"##,
+            result
+        )
+    }
+
+    #[rstest]
+    fn new_brew_all_correct(root: VfsPath) {
+        // Arrange
+        let linux_path = root.join("linux").unwrap();
+        let linux_arm_path = root.join("linux_arm").unwrap();
+        let macos_path = root.join("macos").unwrap();
+        let macos_arm_path = root.join("macos_arm").unwrap();
+
+        // Act
+        let result = Brew::serialize(
+            &root,
+            &linux_path,
+            &linux_arm_path,
             &macos_path,
             &macos_arm_path,
-            "http://localhost",
+            BrewOptions {
+                base_uri: "http://localhost",
+                ..Default::default()
+            },
         );
 
         // Assert
         assert!(result.is_ok());
-        let r = result.unwrap();
+        let r = result.unwrap().content;
         assert!(r.contains("http://localhost/linux-solv.tar.gz"));
+        assert!(r.contains("http://localhost/linux-solv-arm.tar.gz"));
         assert!(r.contains("http://localhost/macos-solv.tar.gz"));
         assert!(r.contains("http://localhost/macos-solv-arm.tar.gz"));
     }
 
+    #[rstest]
+    fn new_brew_desc_warnings(root: VfsPath) {
+        // Arrange
+        let config = CONFIG.replace(
+            "Microsoft Visual Studio solution validator",
+            "The solution validator.",
+        );
+        root.join(CARGO_CONFIG)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(config.as_bytes())
+            .unwrap();
+
+        // Act
+        let result = Brew::serialize(
+            &root,
+            &root.join("linux").unwrap(),
+            &root.join("linux_arm").unwrap(),
+            &root.join("macos").unwrap(),
+            &root.join("macos_arm").unwrap(),
+            BrewOptions {
+                base_uri: "http://localhost",
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // Assert
+        assert!(result.content.contains("desc \"The solution validator.\""));
+        assert_eq!(
+            [
+                "Description shouldn't start with an article",
+                "Description shouldn't end with a full stop"
+            ],
+            result.warnings.as_slice()
+        );
+    }
+
+    #[rstest]
+    #[case::command_line(
+        Some(
+            "class {{ formula }} < Formula\n{{ upper name }} {{ replace version \".\" \"_\" }}{{#each linux}} {{ cpu }}{{/each}}"
+        ),
+        "class Solv < Formula\nSOLV 0_1_13 intel arm"
+    )]
+    #[case::manifest(
+        None,
+        "class Solv < Formula\nsolv 0.1.13 Microsoft Visual Studio solution validator"
+    )]
+    #[trace]
+    fn new_brew_custom_template(
+        root: VfsPath,
+        #[case] template: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        // Arrange
+        let manifest =
+            format!("{CONFIG}\n[package.metadata.releaser.brew]\ntemplate = \"formula.hbs\"\n");
+        root.join(CARGO_CONFIG)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(manifest.as_bytes())
+            .unwrap();
+        root.join("formula.hbs")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(
+                "class {{ formula }} < Formula\n{{ lower name }} {{ version }} {{ description }}"
+                    .as_bytes(),
+            )
+            .unwrap();
+
+        // Act
+        let result = Brew::serialize(
+            &root,
+            &root.join("linux").unwrap(),
+            &root.join("linux_arm").unwrap(),
+            &root.join("macos").unwrap(),
+            &root.join("macos_arm").unwrap(),
+            BrewOptions {
+                base_uri: "http://localhost",
+                template,
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        assert_eq!(expected, result.unwrap().content);
+    }
+
+    #[rstest]
+    fn new_brew_invalid_template(root: VfsPath) {
+        // Arrange
+
+        // Act
+        let result = Brew::serialize(
+            &root,
+            &root.join("linux").unwrap(),
+            &root.join("linux_arm").unwrap(),
+            &root.join("macos").unwrap(),
+            &root.join("macos_arm").unwrap(),
+            BrewOptions {
+                base_uri: "http://localhost",
+                template: Some("{{#if name}}"),
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        assert!(matches!(result, Err(crate::Error::Template(_))));
+    }
+
+    #[rstest]
+    fn new_brew_formula_name(root: VfsPath) {
+        // Arrange
+
+        // Act
+        let result = Brew::serialize(
+            &root,
+            &root.join("linux").unwrap(),
+            &root.join("linux_arm").unwrap(),
+            &root.join("macos").unwrap(),
+            &root.join("macos_arm").unwrap(),
+            BrewOptions {
+                base_uri: "http://localhost",
+                formula_name: Some("solv@0.1"),
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        assert!(result.unwrap().content.contains("class SolvAT01 < Formula"));
+    }
+
+    #[rstest]
+    #[case::other_class("class Solp < Formula\nend\n")]
+    #[case::no_class("{{ name }}")]
+    #[trace]
+    fn new_brew_template_class_mismatch(root: VfsPath, #[case] template: &str) {
+        // Arrange
+
+        // Act
+        let result = Brew::serialize(
+            &root,
+            &root.join("linux").unwrap(),
+            &root.join("linux_arm").unwrap(),
+            &root.join("macos").unwrap(),
+            &root.join("macos_arm").unwrap(),
+            BrewOptions {
+                base_uri: "http://localhost",
+                template: Some(template),
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        assert!(matches!(result, Err(crate::Error::Packaging { .. })));
+    }
+
     #[rstest]
     fn new_brew_no_binaries(root: VfsPath) {
         // Arrange
         let linux_path = root.join("linux1").unwrap();
+        let linux_arm_path = root.join("linux_arm_1").unwrap();
         let macos_path = root.join("macos1").unwrap();
         let macos_arm_path = root.join("macos_arm_1").unwrap();
 
@@ -481,14 +1619,18 @@ end
         let result = Brew::serialize(
             &root,
             &linux_path,
+            &linux_arm_path,
             &macos_path,
             &macos_arm_path,
-            "http://localhost",
+            BrewOptions {
+                base_uri: "http://localhost",
+                ..Default::default()
+            },
         );
 
         // Assert
         assert!(result.is_ok());
-        assert!(result.unwrap().is_empty());
+        assert!(result.unwrap().content.is_empty());
     }
 
     #[rstest]
@@ -505,6 +1647,7 @@ end
             .unwrap();
 
         let linux_path = root.join("linux").unwrap();
+        let linux_arm_path = root.join("linux_arm").unwrap();
         let macos_path = root.join("macos").unwrap();
         let macos_arm_path = root.join("macos_arm").unwrap();
 
@@ -512,15 +1655,515 @@ end
         let result = Brew::serialize(
             &root,
             &linux_path,
+            &linux_arm_path,
             &macos_path,
             &macos_arm_path,
-            "http://localhost",
+            BrewOptions {
+                base_uri: "http://localhost",
+                ..Default::default()
+            },
         );
 
         // Assert
         assert!(result.is_err());
     }
 
+    #[rstest]
+    #[case::defaults(TapOptions::default(), "Formula/solv.rb", "solv 0.1.13")]
+    #[case::custom(
+        TapOptions {
+            formula_name: Some("solv@0.1"),
+            message: Some("Update {name} formula to v{version}"),
+            ..Default::default()
+        },
+        "Formula/solv@0.1.rb",
+        "Update solv@0.1 formula to v0.1.13"
+    )]
+    #[case::cask(
+        TapOptions {
+            formula_name: Some("Solv_App"),
+            cask: true,
+            ..Default::default()
+        },
+        "Casks/solv-app.rb",
+        "solv-app 0.1.13"
+    )]
+    #[trace]
+    fn tap_update_tests(
+        root: VfsPath,
+        #[case] options: TapOptions,
+        #[case] file: &'static str,
+        #[case] message: &'static str,
+    ) {
+        // Arrange
+        let tap = VfsPath::new(MemoryFS::new());
+        let mut vcs = MockVcs::new();
+        vcs.expect_commit()
+            .withf(move |p, o| p == "/tap" && o.message == message && o.files == [file])
+            .times(1)
+            .returning(|_, _| Ok(()));
+        vcs.expect_push()
+            .withf(|p, o| p == "/tap" && *o == PushOptions::default())
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let updater = Tap::new(vcs);
+
+        // Act
+        let actual = updater
+            .update(&VPath::new("/tap", tap.clone()), &root, "formula", options)
+            .unwrap();
+
+        // Assert
+        assert!(actual);
+        assert_eq!("formula", tap.join(file).unwrap().read_to_string().unwrap());
+    }
+
+    #[rstest]
+    fn tap_update_unchanged_test(root: VfsPath) {
+        // Arrange
+        let tap = VfsPath::new(MemoryFS::new());
+        tap.join("Formula").unwrap().create_dir().unwrap();
+        tap.join("Formula/solv.rb")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(b"formula")
+            .unwrap();
+        let mut vcs = MockVcs::new();
+        vcs.expect_commit().never();
+        vcs.expect_push().never();
+        let updater = Tap::new(vcs);
+
+        // Act
+        let actual = updater
+            .update(
+                &VPath::new("/tap", tap),
+                &root,
+                "formula",
+                TapOptions::default(),
+            )
+            .unwrap();
+
+        // Assert
+        assert!(!actual);
+    }
+
+    #[cfg(feature = "native-git")]
+    #[rstest]
+    #[case::formula(false, "Formula/solv.rb")]
+    #[case::cask(true, "Casks/solv.rb")]
+    #[trace]
+    fn tap_update_pushes_into_remote(root: VfsPath, #[case] cask: bool, #[case] file: &str) {
+        use crate::native_git::NativeGit;
+        use git2::{Repository, Signature};
+        use tempfile::TempDir;
+
+        // Arrange
+        let remote_dir = TempDir::new().unwrap();
+        let remote = Repository::init_bare(remote_dir.path()).unwrap();
+        let tree = remote
+            .find_tree(remote.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let signature = Signature::now("releaser", "releaser@example.com").unwrap();
+        remote
+            .commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])
+            .unwrap();
+        let tap_dir = TempDir::new().unwrap();
+        let repo = Repository::clone(remote_dir.path().to_str().unwrap(), tap_dir.path()).unwrap();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("user.name", "releaser").unwrap();
+            config
+                .set_str("user.email", "releaser@example.com")
+                .unwrap();
+        }
+        let tap = VfsPath::new(vfs::PhysicalFS::new(tap_dir.path()));
+        let updater = Tap::new(NativeGit);
+        let options = TapOptions {
+            cask,
+            ..Default::default()
+        };
+
+        // Act
+        let actual = updater
+            .update(
+                &VPath::new(tap_dir.path().to_str().unwrap(), tap),
+                &root,
+                "formula",
+                options,
+            )
+            .unwrap();
+
+        // Assert
+        assert!(actual);
+        let branch = repo.head().unwrap().name().unwrap().to_string();
+        let pushed = remote
+            .find_commit(remote.refname_to_id(&branch).unwrap())
+            .unwrap();
+        assert_eq!(repo.head().unwrap().target().unwrap(), pushed.id());
+        assert_eq!(Some("solv 0.1.13"), pushed.message());
+        let entry = pushed
+            .tree()
+            .unwrap()
+            .get_path(std::path::Path::new(file))
+            .unwrap();
+        let blob = remote.find_blob(entry.id()).unwrap();
+        assert_eq!(b"formula", blob.content());
+    }
+
+    #[test]
+    fn serialize_cask_test() {
+        // Arrange
+        let cask = Cask {
+            token: "solv".to_string(),
+            name: "solv".to_string(),
+            description: "Solution validator".to_string(),
+            homepage: Some("https://github.com/aegoroff/solv".to_string()),
+            version: "0.4.0".to_string(),
+            packages: vec![
+                BrewPackage {
+                    package: Package {
+                        url: "https://github.com/aegoroff/solv/releases/download/v0.4.0/solv-arm64.dmg".to_string(),
+                        hash: "fb5c2f5d41c7d3485898de9905736dc8c540a912dc95d3a55bd9360901689811".to_string(),
+                    },
+                    cpu: "arm".to_string(),
+                },
+                BrewPackage {
+                    package: Package {
+                        url: "https://github.com/aegoroff/solv/releases/download/v0.4.0/solv-amd64.dmg".to_string(),
+                        hash: "9a6c8144ed77cd5e2b88031109ac4285ca08e8c644f3d022a389359470721a7b".to_string(),
+                    },
+                    cpu: "intel".to_string(),
+                },
+            ],
+            apps: vec!["Solv.app".to_string()],
+            binaries: vec!["solv".to_string()],
+            livecheck: Some("https://github.com/aegoroff/solv".to_string()),
+            ..Default::default()
+        };
+
+        // Act
+        let result = serialize_brew(&cask, CASK_TEMPLATE).unwrap();
+
+        // Assert
+        assert_eq!(
+            r#"# typed: false
+# frozen_string_literal: true
+# This file was generated by releaser. DO NOT EDIT.
+cask "solv" do
+  version "0.4.0"
+
+  on_arm do
+    sha256 "fb5c2f5d41c7d3485898de9905736dc8c540a912dc95d3a55bd9360901689811"
+    url "https://github.com/aegoroff/solv/releases/download/v0.4.0/solv-arm64.dmg"
+  end
+
+  on_intel do
+    sha256 "9a6c8144ed77cd5e2b88031109ac4285ca08e8c644f3d022a389359470721a7b"
+    url "https://github.com/aegoroff/solv/releases/download/v0.4.0/solv-amd64.dmg"
+  end
+
+  name "solv"
+  desc "Solution validator"
+  homepage "https://github.com/aegoroff/solv"
+
+  livecheck do
+    url "https://github.com/aegoroff/solv"
+    strategy :github_latest
+  end
+
+  app "Solv.app"
+  binary "solv"
+end
+"#,
+            result
+        );
+    }
+
+    #[rstest]
+    #[case::dmg_app(
+        &["solv.dmg"],
+        &["Solv.app"],
+        &["  url \"http://localhost/solv.dmg\"", "app \"Solv.app\""],
+        &["on_arm do", "on_intel do", "binary", "pkg", "uninstall"]
+    )]
+    #[case::pkg(
+        &["solv.pkg", "solv.pkg"],
+        &[],
+        &[
+            "on_arm do",
+            "on_intel do",
+            "pkg \"solv.pkg\"",
+            "uninstall pkgutil: \"com.egoroff.solv\""
+        ],
+        &["binary", "app"]
+    )]
+    #[case::zip(
+        &["solv.zip"],
+        &[],
+        &["binary \"solv\"", "url \"https://github.com/aegoroff/solv\""],
+        &["app", "pkg", "uninstall"]
+    )]
+    #[trace]
+    fn new_cask_tests(
+        root: VfsPath,
+        #[case] files: &[&str],
+        #[case] apps: &[&str],
+        #[case] contains: &[&str],
+        #[case] not_contains: &[&str],
+    ) {
+        // Arrange
+        let dirs = ["cask_intel", "cask_arm"];
+        for (dir, file) in dirs.iter().zip(files) {
+            let dir = root.join(dir).unwrap();
+            dir.create_dir().unwrap();
+            dir.join(file)
+                .unwrap()
+                .create_file()
+                .unwrap()
+                .write_all(b"123")
+                .unwrap();
+        }
+        let apps = apps.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        // Act
+        let result = Cask::serialize(
+            &root,
+            &root.join(dirs[0]).unwrap(),
+            &root.join(dirs[1]).unwrap(),
+            &apps,
+            BrewOptions {
+                base_uri: "http://localhost",
+                pkg_id: Some("com.egoroff.solv"),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .content;
+
+        // Assert
+        assert!(result.starts_with("# typed: false"));
+        assert!(result.contains("cask \"solv\" do"));
+        for c in contains {
+            assert!(result.contains(c), "{c} not found");
+        }
+        for c in not_contains {
+            assert!(!result.contains(c), "{c} found");
+        }
+    }
+
+    #[test]
+    fn serialize_cask_single_package_test() {
+        // Arrange
+        let cask = Cask {
+            token: "solv".to_string(),
+            name: "solv".to_string(),
+            description: "Solution validator".to_string(),
+            version: "0.4.0".to_string(),
+            packages: vec![BrewPackage {
+                package: Package {
+                    url: "https://github.com/aegoroff/solv/releases/download/v0.4.0/solv.dmg"
+                        .to_string(),
+                    hash: "fb5c2f5d41c7d3485898de9905736dc8c540a912dc95d3a55bd9360901689811"
+                        .to_string(),
+                },
+                cpu: "intel".to_string(),
+            }],
+            apps: vec!["Solv.app".to_string()],
+            ..Default::default()
+        };
+
+        // Act
+        let result = serialize_brew(&cask, CASK_TEMPLATE).unwrap();
+
+        // Assert
+        assert_eq!(
+            r#"# typed: false
+# frozen_string_literal: true
+# This file was generated by releaser. DO NOT EDIT.
+cask "solv" do
+  version "0.4.0"
+
+  sha256 "fb5c2f5d41c7d3485898de9905736dc8c540a912dc95d3a55bd9360901689811"
+  url "https://github.com/aegoroff/solv/releases/download/v0.4.0/solv.dmg"
+
+  name "solv"
+  desc "Solution validator"
+
+  app "Solv.app"
+end
+"#,
+            result
+        );
+    }
+
+    #[rstest]
+    #[case::option(Some("cask \"{{ token }}\" do\nend\n"), "cask \"solv\" do\nend\n")]
+    #[case::manifest(None, "cask \"solv\" # manifest\n")]
+    #[trace]
+    fn new_cask_custom_template(
+        root: VfsPath,
+        #[case] cask_template: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        // Arrange
+        let config = format!(
+            "{CONFIG}\n[package.metadata.releaser.brew]\ntemplate = \"formula.hbs\"\ncask-template = \"cask.hbs\"\n"
+        );
+        root.join(CARGO_CONFIG)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(config.as_bytes())
+            .unwrap();
+        root.join("cask.hbs")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(b"cask \"{{ token }}\" # manifest\n")
+            .unwrap();
+        let dir = root.join("cask_intel").unwrap();
+        dir.create_dir().unwrap();
+        dir.join("solv.dmg")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(b"123")
+            .unwrap();
+
+        // Act
+        let result = Cask::serialize(
+            &root,
+            &dir,
+            &root.join("cask_arm").unwrap(),
+            &[],
+            BrewOptions {
+                base_uri: "http://localhost",
+                template: Some("class Solv < Formula\nend\n"),
+                cask_template,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .content;
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn desc_warnings_cask_test() {
+        // Arrange
+
+        // Act
+        let actual = desc_warnings(CASK, "solv", "");
+
+        // Assert
+        assert_eq!(["Cask should have a desc"], actual.as_slice());
+    }
+
+    #[rstest]
+    #[case::manifest(true)]
+    #[case::missing(false)]
+    #[trace]
+    fn new_cask_pkg_id(root: VfsPath, #[case] in_manifest: bool) {
+        // Arrange
+        if in_manifest {
+            let config = format!(
+                "{CONFIG}\n[package.metadata.releaser.brew]\npkg-id = \"com.egoroff.solv\"\n"
+            );
+            root.join(CARGO_CONFIG)
+                .unwrap()
+                .create_file()
+                .unwrap()
+                .write_all(config.as_bytes())
+                .unwrap();
+        }
+        let dir = root.join("cask_intel").unwrap();
+        dir.create_dir().unwrap();
+        dir.join("solv.pkg")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(b"123")
+            .unwrap();
+
+        // Act
+        let result = Cask::serialize(
+            &root,
+            &dir,
+            &root.join("cask_arm").unwrap(),
+            &[],
+            BrewOptions {
+                base_uri: "http://localhost",
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        if in_manifest {
+            let content = result.unwrap().content;
+            assert!(
+                content
+                    .contains("  pkg \"solv.pkg\"\n\n  uninstall pkgutil: \"com.egoroff.solv\"\n")
+            );
+        } else {
+            assert!(matches!(result, Err(crate::Error::Packaging { .. })));
+        }
+    }
+
+    #[rstest]
+    fn new_cask_no_packages(root: VfsPath) {
+        // Arrange
+
+        // Act
+        let result = Cask::serialize(
+            &root,
+            &root.join("linux").unwrap(),
+            &root.join("macos").unwrap(),
+            &[],
+            BrewOptions::default(),
+        );
+
+        // Assert
+        assert_eq!("", result.unwrap().content);
+    }
+
+    #[test]
+    fn pkg_installer_different_names_test() {
+        // Arrange
+        let packages = ["http://x/a-arm.pkg", "http://x/a-intel.pkg"].map(|url| BrewPackage {
+            package: Package {
+                url: url.to_string(),
+                hash: String::new(),
+            },
+            cpu: String::new(),
+        });
+
+        // Act
+        let result = pkg_installer(&packages);
+
+        // Assert
+        assert!(matches!(result, Err(crate::Error::Packaging { .. })));
+    }
+
+    #[rstest]
+    #[case::plain("solv", "solv")]
+    #[case::uppercase("Solv", "solv")]
+    #[case::underscore("foo_bar", "foo-bar")]
+    #[case::space("My Tool", "my-tool")]
+    #[case::versioned("tool@2", "tool@2")]
+    #[trace]
+    fn cask_token_tests(#[case] name: &str, #[case] expected: &str) {
+        // Arrange
+
+        // Act
+        let actual = cask_token(name);
+
+        // Assert
+        assert_eq!(expected, actual);
+    }
+
     #[fixture]
     fn root() -> VfsPath {
         let root = VfsPath::new(MemoryFS::new());
@@ -528,6 +2171,7 @@ end
         root.join("linux").unwrap().create_dir().unwrap();
         root.join("macos").unwrap().create_dir().unwrap();
         root.join("macos_arm").unwrap().create_dir().unwrap();
+        root.join("linux_arm").unwrap().create_dir().unwrap();
         root.join(CARGO_CONFIG)
             .unwrap()
             .create_file()
@@ -544,6 +2188,15 @@ end
             .write_all("123".as_bytes())
             .unwrap();
 
+        root.join("linux_arm")
+            .unwrap()
+            .join("linux-solv-arm.tar.gz")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all("1234".as_bytes())
+            .unwrap();
+
         root.join("macos")
             .unwrap()
             .join("macos-solv.tar.gz")
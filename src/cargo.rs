@@ -1,10 +1,40 @@
-use crate::{PublishOptions, Publisher};
-use color_eyre::eyre::Result;
+use crate::Result;
+use crate::{PublishError, PublishOptions, Publisher};
+use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, ExitStatus, Stdio};
 
 const TOOL: &str = "cargo";
 
+/// Output fragments (lowercase) of failures that won't go away if publish retried
+const PERMANENT_MARKERS: [&str; 9] = [
+    "already exists",
+    "already uploaded",
+    "failed to verify package",
+    "failed to prepare local package",
+    "no token found",
+    "status 401",
+    "status 403",
+    "got 401",
+    "got 403",
+];
+
+/// Output fragments (lowercase) of registry rate limit, registry server and network failures
+const TRANSIENT_MARKERS: [&str; 12] = [
+    "status 429",
+    "got 429",
+    "too many requests",
+    "status 50",
+    "got 50",
+    "spurious network error",
+    "timed out",
+    "connection reset",
+    "connection refused",
+    "failed to connect",
+    "could not resolve host",
+    "couldn't resolve host",
+];
+
 #[derive(Default)]
 pub struct Cargo;
 
@@ -19,15 +49,170 @@ impl Publisher for Cargo {
             child.arg("--manifest-path").arg(manifest_path);
         }
 
-        if options.all_features {
-            child.arg("--all-features");
-        }
+        add_options(child, &options);
+        run(child)
+    }
+
+    fn package<'a>(
+        &'a self,
+        path: &'a str,
+        crates: &'a [String],
+        options: PublishOptions<'a>,
+    ) -> Result<()> {
+        let mut process = Command::new(TOOL);
+        let child = process.current_dir(path).arg("package");
 
-        if options.no_verify {
-            child.arg("--no-verify");
+        for crt in crates {
+            child.arg("--package").arg(crt);
         }
 
-        child.spawn()?.wait()?;
+        add_options(child, &options);
+        run(child)
+    }
+}
+
+/// Adds options shared by cargo publish and cargo package commands
+fn add_options(child: &mut Command, options: &PublishOptions) {
+    if let Some(registry) = options.registry {
+        child.arg("--registry").arg(registry);
+    }
+
+    if options.all_features {
+        child.arg("--all-features");
+    }
+
+    if options.no_verify {
+        child.arg("--no-verify");
+    }
+
+    if !options.features.is_empty() {
+        child.arg("--features").arg(options.features.join(","));
+    }
+
+    if options.no_default_features {
+        child.arg("--no-default-features");
+    }
+
+    if let Some(target) = options.target {
+        child.arg("--target").arg(target);
+    }
+
+    if options.locked {
+        child.arg("--locked");
+    }
+
+    if let Some(jobs) = options.jobs {
+        child.arg("--jobs").arg(jobs.to_string());
+    }
+
+    if options.allow_dirty {
+        child.arg("--allow-dirty");
+    }
+}
+
+fn run(child: &mut Command) -> Result<()> {
+    // stderr captured to find out failure reason but still shown to user
+    let mut process = child.stderr(Stdio::piped()).spawn()?;
+    let mut output = String::new();
+    if let Some(stderr) = process.stderr.take() {
+        for line in BufReader::new(stderr).lines() {
+            let line = line?;
+            eprintln!("{line}");
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+    let status = process.wait()?;
+    if status.success() {
         Ok(())
+    } else {
+        Err(classify(&output, status).into())
+    }
+}
+
+/// Decides whether failure may be retried using error lines of cargo output.
+/// Progress messages and warnings (compile ones for example) aren't taken into account.
+/// Unknown failures considered permanent
+fn classify(output: &str, status: ExitStatus) -> PublishError {
+    // error line and its causes follow progress messages and warnings
+    let errors = output
+        .lines()
+        .skip_while(|l| !l.starts_with("error"))
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<&str>>();
+    let message = if errors.is_empty() {
+        format!("{TOOL} exited with {status}")
+    } else {
+        errors.join(" ")
+    };
+    let lowercase = message.to_lowercase();
+    let has = |markers: &[&str]| markers.iter().any(|m| lowercase.contains(m));
+    if !has(&PERMANENT_MARKERS) && has(&TRANSIENT_MARKERS) {
+        PublishError::Transient(message)
+    } else {
+        PublishError::Permanent(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::rate_limit(
+        "error: failed to publish to registry at https://crates.io\n\nCaused by:\n  the remote server responded with an error (status 429 Too Many Requests): You have published too many new crates in a short period of time",
+        true
+    )]
+    #[case::server_error("error: failed to get a 200 OK response, got 503", true)]
+    #[case::network(
+        "warning: spurious network error (3 tries remaining): [28] Timeout was reached\nerror: failed to publish to registry at https://crates.io\n\nCaused by:\n  [28] Timeout was reached (Operation timed out after 30000 milliseconds)",
+        true
+    )]
+    #[case::network_warning_only(
+        "warning: spurious network error (3 tries remaining): [28] Timeout was reached\nerror: failed to prepare local package for uploading",
+        false
+    )]
+    #[case::compile_warning(
+        "warning: field `x` already exists in struct\nerror: failed to get a 200 OK response, got 502",
+        true
+    )]
+    #[case::version_exists(
+        "error: failed to publish to registry at https://crates.io\n\nCaused by:\n  the remote server responded with an error (status 400 Bad Request): crate solv@0.16.1 already exists on crates.io index",
+        false
+    )]
+    #[case::verification(
+        "error: failed to verify package tarball\n\nCaused by:\n  failed to compile `solv v0.16.1`",
+        false
+    )]
+    #[case::unauthorized(
+        "error: the remote server responded with an error (status 403 Forbidden): must be logged in",
+        false
+    )]
+    #[case::unknown("error: something went wrong", false)]
+    #[case::empty("", false)]
+    #[trace]
+    fn classify_tests(#[case] output: &str, #[case] expected: bool) {
+        // Arrange
+        let status = failed_status();
+
+        // Act
+        let actual = classify(output, status);
+
+        // Assert
+        assert_eq!(expected, actual.is_transient());
+    }
+
+    #[cfg(unix)]
+    fn failed_status() -> ExitStatus {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatus::from_raw(1 << 8)
+    }
+
+    #[cfg(windows)]
+    fn failed_status() -> ExitStatus {
+        use std::os::windows::process::ExitStatusExt;
+        ExitStatus::from_raw(1)
     }
 }
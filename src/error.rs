@@ -0,0 +1,182 @@
+use std::fmt;
+
+use thiserror::Error as ThisError;
+
+type Source = Box<dyn std::error::Error + Send + Sync>;
+
+/// Result of library operations
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Library error
+#[derive(Debug, ThisError)]
+#[non_exhaustive]
+pub enum Error {
+    /// Manifest or cargo configuration cannot be read, parsed or has invalid content
+    #[error("{message}")]
+    Config {
+        message: String,
+        #[source]
+        source: Option<Source>,
+    },
+    /// Version cannot be parsed or incremented
+    #[error("Invalid version '{version}'")]
+    Version {
+        version: String,
+        #[source]
+        source: semver::Error,
+    },
+    /// Workspace members depend on each other so that publish order cannot be determined
+    #[error(
+        "Workspace members {} depend on each other so they cannot be published",
+        .0.join(", ")
+    )]
+    DependencyCycle(Vec<String>),
+    /// Version control operation (commit, tag, push etc.) failed
+    #[error("{message}")]
+    Vcs {
+        message: String,
+        #[source]
+        source: Option<Source>,
+    },
+    /// Registry index cannot be queried
+    #[error("{message}")]
+    Registry {
+        message: String,
+        #[source]
+        source: Option<Source>,
+    },
+    /// Crate publishing failed
+    #[error(transparent)]
+    Publish(#[from] PublishError),
+    /// Crates packaging verification failed or package manager definition cannot be created
+    #[error("{message}")]
+    Packaging {
+        message: String,
+        #[source]
+        source: Option<Source>,
+    },
+    /// Package manager definition template cannot be rendered
+    #[error("Template rendering failed")]
+    Template(#[source] Source),
+    /// Release hook command failed
+    #[error("{0}")]
+    Hook(String),
+    /// File system operation failed
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Virtual file system operation failed
+    #[error(transparent)]
+    Fs(#[from] vfs::VfsError),
+}
+
+/// Failed publish. Transient failures (registry rate limit, network or registry server errors)
+/// may succeed if retried later while permanent ones (version already exists,
+/// verification failure etc.) won't
+#[derive(Debug, ThisError)]
+pub enum PublishError {
+    #[error("Publish failed (transient error): {0}")]
+    Transient(String),
+    #[error("Publish failed: {0}")]
+    Permanent(String),
+}
+
+impl PublishError {
+    /// Whether publish that failed this way may succeed if retried
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Transient(_))
+    }
+}
+
+impl Error {
+    pub(crate) fn config(message: impl Into<String>) -> Self {
+        Self::Config {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub(crate) fn version(version: &str, source: semver::Error) -> Self {
+        Self::Version {
+            version: version.to_string(),
+            source,
+        }
+    }
+
+    pub(crate) fn toml(path: impl fmt::Display, source: impl Into<Source>) -> Self {
+        Self::Config {
+            message: format!("Invalid TOML in {path}"),
+            source: Some(source.into()),
+        }
+    }
+
+    pub(crate) fn vcs(message: impl Into<String>) -> Self {
+        Self::Vcs {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub(crate) fn registry(message: impl Into<String>) -> Self {
+        Self::Registry {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub(crate) fn packaging(message: impl Into<String>) -> Self {
+        Self::Packaging {
+            message: message.into(),
+            source: None,
+        }
+    }
+}
+
+impl From<url::ParseError> for Error {
+    fn from(e: url::ParseError) -> Self {
+        Self::Config {
+            message: "Invalid URL".to_string(),
+            source: Some(Box::new(e)),
+        }
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(e: ureq::Error) -> Self {
+        Self::Registry {
+            message: "Registry index request failed".to_string(),
+            source: Some(Box::new(e)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Packaging {
+            message: "Package manager definition serialization failed".to_string(),
+            source: Some(Box::new(e)),
+        }
+    }
+}
+
+impl From<handlebars::RenderError> for Error {
+    fn from(e: handlebars::RenderError) -> Self {
+        Self::Template(Box::new(e))
+    }
+}
+
+impl From<handlebars::TemplateError> for Error {
+    fn from(e: handlebars::TemplateError) -> Self {
+        Self::Template(Box::new(e))
+    }
+}
+
+#[cfg(feature = "native-git")]
+impl From<git2::Error> for Error {
+    fn from(e: git2::Error) -> Self {
+        Self::Vcs {
+            message: e.message().to_string(),
+            source: Some(Box::new(e)),
+        }
+    }
+}
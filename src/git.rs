@@ -1,55 +1,219 @@
-use color_eyre::eyre::Result;
-use std::process::Command;
+use crate::{Error, Result};
+use std::process::{Command, Stdio};
 
-use crate::Vcs;
+use crate::{CommitOptions, PushOptions, TagOptions, Vcs};
 
 const TOOL: &str = "git";
+const HEAD: &str = "HEAD";
 
 #[derive(Default)]
 pub struct Git;
 
 impl Vcs for Git {
-    fn commit(&self, path: &str, message: &str) -> Result<()> {
-        let mut child = Command::new(TOOL)
+    fn commit<'a>(&'a self, path: &'a str, options: CommitOptions<'a>) -> Result<()> {
+        // Stage files so that new ones (changelog for example) become tracked.
+        // Ignored files (Cargo.lock in libraries for example) aren't staged
+        // because git add fails on them
+        let ignored = Command::new(TOOL)
             .current_dir(path)
-            .arg("commit")
-            .arg("-a")
+            .arg("check-ignore")
+            .arg("--")
+            .args(options.files)
+            .output()?;
+        // exit code 1 means none of the files is ignored
+        if !matches!(ignored.status.code(), Some(0 | 1)) {
+            return Err(Error::vcs(format!(
+                "Failed to check ignored files: {}",
+                String::from_utf8_lossy(&ignored.stderr).trim()
+            )));
+        }
+        let ignored = String::from_utf8_lossy(&ignored.stdout);
+        let to_stage = options
+            .files
+            .iter()
+            .filter(|f| !ignored.lines().any(|i| i == f.as_str()))
+            .collect::<Vec<&String>>();
+        if !to_stage.is_empty() {
+            let mut process = Command::new(TOOL);
+            let child = process
+                .current_dir(path)
+                .arg("add")
+                .arg("--")
+                .args(to_stage);
+            run(child, "add")?;
+        }
+
+        let tracked = Command::new(TOOL)
+            .current_dir(path)
+            .arg("ls-files")
+            .arg("--")
+            .args(options.files)
+            .output()?;
+        if !tracked.status.success() {
+            return Err(Error::vcs(format!(
+                "Failed to list tracked files: {}",
+                String::from_utf8_lossy(&tracked.stderr).trim()
+            )));
+        }
+        let tracked = String::from_utf8_lossy(&tracked.stdout);
+        if tracked.trim().is_empty() {
+            return Err(Error::vcs("None of the files to commit is tracked by git"));
+        }
+
+        let mut process = Command::new(TOOL);
+        let child = process.current_dir(path).arg("commit");
+
+        if options.sign {
+            child.arg("-S");
+        }
+
+        child
             .arg("-m")
-            .arg(message)
-            .spawn()?;
-        child.wait()?;
-        Ok(())
+            .arg(options.message)
+            .arg("--")
+            .args(tracked.lines());
+        run(child, "commit")
     }
 
-    fn create_tag(&self, path: &str, tag: &str) -> Result<()> {
-        let mut child = Command::new(TOOL)
-            .current_dir(path)
-            .arg("tag")
-            .arg(tag)
-            .spawn()?;
-        child.wait()?;
-        Ok(())
+    fn create_tag<'a>(
+        &'a self,
+        path: &'a str,
+        tag: &'a str,
+        options: TagOptions<'a>,
+    ) -> Result<()> {
+        let mut process = Command::new(TOOL);
+        let child = process.current_dir(path).arg("tag");
+
+        if options.sign {
+            // signed tag must be annotated so use tag name as message if none set
+            child
+                .arg("-s")
+                .arg("-m")
+                .arg(options.message.unwrap_or(tag));
+        } else if let Some(message) = options.message {
+            child.arg("-a").arg("-m").arg(message);
+        }
+
+        child.arg(tag);
+        run(child, "tag")
     }
 
-    fn push_tag(&self, path: &str, tag: &str) -> Result<()> {
-        let mut child = Command::new(TOOL)
+    fn push_tag<'a>(&'a self, path: &'a str, tag: &'a str, options: PushOptions<'a>) -> Result<()> {
+        let mut process = Command::new(TOOL);
+        let child = process
             .current_dir(path)
             .arg("push")
-            .arg("origin")
+            .arg(options.remote)
             .arg("tag")
-            .arg(tag)
-            .spawn()?;
-        child.wait()?;
-        Ok(())
+            .arg(tag);
+        run(child, "push tag")
+    }
+
+    fn push<'a>(&'a self, path: &'a str, options: PushOptions<'a>) -> Result<()> {
+        let mut process = Command::new(TOOL);
+        let child = process.current_dir(path).arg("push").arg(options.remote);
+
+        if let Some(branch) = options.branch {
+            child.arg(head_refspec(branch));
+        }
+
+        run(child, "push")
     }
 
-    fn push(&self, path: &str) -> Result<()> {
-        let mut child = Command::new(TOOL)
+    fn push_atomic<'a>(
+        &'a self,
+        path: &'a str,
+        tag: &'a str,
+        options: PushOptions<'a>,
+    ) -> Result<()> {
+        let branch = options
+            .branch
+            .map_or_else(|| HEAD.to_string(), head_refspec);
+        let mut process = Command::new(TOOL);
+        let child = process
             .current_dir(path)
             .arg("push")
-            .arg("origin")
-            .spawn()?;
-        child.wait()?;
+            .arg("--atomic")
+            .arg(options.remote)
+            .arg(branch)
+            .arg(format!("refs/tags/{tag}"));
+        run(child, "push --atomic")
+    }
+
+    fn is_clean(&self, path: &str) -> Result<bool> {
+        let output = Command::new(TOOL)
+            .current_dir(path)
+            .arg("status")
+            .arg("--porcelain")
+            .arg("--untracked-files=no")
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::vcs(format!(
+                "Failed to get git status: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout.iter().all(u8::is_ascii_whitespace))
+    }
+
+    fn last_tag(&self, path: &str) -> Result<Option<String>> {
+        // fails if there are no tags
+        let output = Command::new(TOOL)
+            .current_dir(path)
+            .arg("describe")
+            .arg("--tags")
+            .arg("--abbrev=0")
+            .output()?;
+        let tag = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(Some(tag).filter(|t| output.status.success() && !t.is_empty()))
+    }
+
+    fn commits_since<'a>(&'a self, path: &'a str, tag: Option<&'a str>) -> Result<u32> {
+        let range = tag.map_or_else(|| HEAD.to_string(), |t| format!("{t}..{HEAD}"));
+        let output = Command::new(TOOL)
+            .current_dir(path)
+            .arg("rev-list")
+            .arg("--count")
+            .arg(range)
+            .arg("--")
+            .arg(".")
+            .output()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            return Err(Error::vcs(format!(
+                "Failed to count commits: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        stdout
+            .trim()
+            .parse()
+            .map_err(|_| Error::vcs(format!("Unexpected commits count {}", stdout.trim())))
+    }
+}
+
+/// Runs git command. Output is shown to user while stderr is captured
+/// and not shown so that failure reason (rejected push for example) is in the error
+fn run(child: &mut Command, action: &str) -> Result<()> {
+    let output = child
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::piped())
+        .output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if output.status.success() {
         Ok(())
+    } else {
+        let reason = stderr.trim();
+        let message = if reason.is_empty() {
+            format!("{TOOL} {action} exited with {}", output.status)
+        } else {
+            format!("{TOOL} {action} failed: {reason}")
+        };
+        Err(Error::vcs(message))
     }
 }
+
+/// Creates refspec that pushes current HEAD into branch specified
+fn head_refspec(branch: &str) -> String {
+    format!("{HEAD}:refs/heads/{branch}")
+}
@@ -0,0 +1,339 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use clap::ValueEnum;
+use vfs::VfsPath;
+
+use crate::CARGO_CONFIG;
+use crate::Result;
+use crate::version_iter::VersionIter;
+
+const INDENT: &str = "  ";
+
+/// Workspace dependency graph output format
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    /// Indented tree of members and their dependencies
+    #[default]
+    Tree,
+}
+
+/// Dependencies between workspace members. Only normal dependencies
+/// with version set are considered because only they affect publish order
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    members: Vec<String>,
+    dependencies: Vec<(String, String)>,
+    publish_order: Vec<String>,
+    cycles: Vec<Vec<String>>,
+}
+
+impl DependencyGraph {
+    /// Reads graph of workspace which root directory specified
+    pub fn open(root: &VfsPath) -> Result<Self> {
+        let config = root.join(CARGO_CONFIG)?;
+        let mut it = VersionIter::open(&config)?;
+        let members = it.members().into_iter().cloned().collect();
+        it.by_ref().for_each(drop);
+        Ok(Self {
+            members,
+            dependencies: it.dependencies(),
+            publish_order: it.topo_sort().unwrap_or_default(),
+            cycles: it.cycles(),
+        })
+    }
+
+    /// Members in the order they're listed in workspace config
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+
+    /// Dependencies as (dependency, dependent) pairs
+    pub fn dependencies(&self) -> &[(String, String)] {
+        &self.dependencies
+    }
+
+    /// Order members published in. Empty if there are cycles
+    pub fn publish_order(&self) -> &[String] {
+        &self.publish_order
+    }
+
+    /// Groups of members that depend on each other
+    pub fn cycles(&self) -> &[Vec<String>] {
+        &self.cycles
+    }
+
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.dot(),
+            GraphFormat::Mermaid => self.mermaid(),
+            GraphFormat::Tree => self.tree(),
+        }
+    }
+
+    fn dot(&self) -> String {
+        let mut out = String::from("digraph workspace {\n");
+        let _ = writeln!(out, "{INDENT}// publish order: {}", self.order_summary());
+        for cycle in &self.cycles {
+            let _ = writeln!(out, "{INDENT}// cycle: {}", cycle.join(", "));
+        }
+        for member in &self.members {
+            let _ = writeln!(out, "{INDENT}\"{member}\";");
+        }
+        for (dependency, dependent) in &self.dependencies {
+            let style = if self.in_cycle(dependency, dependent) {
+                " [color=red]"
+            } else {
+                ""
+            };
+            let _ = writeln!(out, "{INDENT}\"{dependent}\" -> \"{dependency}\"{style};");
+        }
+        out.push('}');
+        out
+    }
+
+    fn mermaid(&self) -> String {
+        let mut out = String::from("graph TD\n");
+        let _ = writeln!(out, "{INDENT}%% publish order: {}", self.order_summary());
+        for cycle in &self.cycles {
+            let _ = writeln!(out, "{INDENT}%% cycle: {}", cycle.join(", "));
+        }
+        // member names may contain characters not allowed in node ids
+        let id = |name: &str| self.members.iter().position(|m| m == name).unwrap_or(0);
+        for (i, member) in self.members.iter().enumerate() {
+            let _ = writeln!(out, "{INDENT}n{i}[\"{member}\"]");
+        }
+        for (dependency, dependent) in &self.dependencies {
+            let _ = writeln!(out, "{INDENT}n{} --> n{}", id(dependent), id(dependency));
+        }
+        out.trim_end().to_string()
+    }
+
+    /// Renders members top-down from dependent to its dependencies i.e. member nothing
+    /// depends on is at top level and each member's dependencies are nested one level deeper
+    fn tree(&self) -> String {
+        let dependencies = self
+            .dependencies
+            .iter()
+            .map(|(dependency, _)| dependency)
+            .collect::<HashSet<_>>();
+        let mut out = String::new();
+        let mut visited = HashSet::new();
+        // members nothing depends on first then members reachable from cycles only
+        let roots = self
+            .members
+            .iter()
+            .filter(|m| !dependencies.contains(m))
+            .chain(self.members.iter());
+        for root in roots {
+            if !visited.contains(root) {
+                self.write_node(&mut out, root, &mut vec![], &mut visited);
+            }
+        }
+        let _ = write!(out, "\nPublish order: {}", self.order_summary());
+        for cycle in &self.cycles {
+            let _ = write!(out, "\nCycle: {}", cycle.join(", "));
+        }
+        out
+    }
+
+    /// Writes member with its dependencies nested. Dependencies of member already
+    /// written (shared by several dependents) aren't repeated
+    fn write_node<'a>(
+        &'a self,
+        out: &mut String,
+        member: &'a String,
+        ancestors: &mut Vec<&'a String>,
+        visited: &mut HashSet<&'a String>,
+    ) {
+        let indent = INDENT.repeat(ancestors.len());
+        if ancestors.contains(&member) {
+            let _ = writeln!(out, "{indent}{member} (cycle)");
+            return;
+        }
+        let mut dependencies = self
+            .dependencies
+            .iter()
+            .filter(|(_, d)| d == member)
+            .peekable();
+        if visited.contains(member) && dependencies.peek().is_some() {
+            let _ = writeln!(out, "{indent}{member} (see above)");
+            return;
+        }
+        let _ = writeln!(out, "{indent}{member}");
+        visited.insert(member);
+        ancestors.push(member);
+        for (dependency, _) in dependencies {
+            self.write_node(out, dependency, ancestors, visited);
+        }
+        ancestors.pop();
+    }
+
+    fn in_cycle(&self, dependency: &String, dependent: &String) -> bool {
+        self.cycles
+            .iter()
+            .any(|c| c.contains(dependency) && c.contains(dependent))
+    }
+
+    fn order_summary(&self) -> String {
+        if self.cycles.is_empty() {
+            self.publish_order.join(", ")
+        } else {
+            "none because of dependency cycles".to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_in_result)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use rstest::{fixture, rstest};
+    use vfs::MemoryFS;
+
+    #[rstest]
+    fn open_test(root: VfsPath) {
+        // Arrange
+
+        // Act
+        let graph = DependencyGraph::open(&root).unwrap();
+
+        // Assert
+        assert_eq!(vec!["a", "b", "c"], graph.members());
+        assert_eq!(
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("a".to_string(), "c".to_string()),
+                ("b".to_string(), "c".to_string())
+            ],
+            graph.dependencies()
+        );
+        assert_eq!(vec!["a", "b", "c"], graph.publish_order());
+        assert!(graph.cycles().is_empty());
+    }
+
+    #[rstest]
+    fn open_independent_member_test(root: VfsPath) {
+        // Arrange
+        write_member(&root, "c", "c", &[]);
+
+        // Act
+        let graph = DependencyGraph::open(&root).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![("a".to_string(), "b".to_string())],
+            graph.dependencies()
+        );
+        assert_eq!(3, graph.publish_order().len());
+        assert!(graph.publish_order().contains(&"c".to_string()));
+    }
+
+    #[rstest]
+    #[case::dot(
+        GraphFormat::Dot,
+        "digraph workspace {\n  // publish order: a, b, c\n  \"a\";\n  \"b\";\n  \"c\";\n  \"b\" -> \"a\";\n  \"c\" -> \"a\";\n  \"c\" -> \"b\";\n}"
+    )]
+    #[case::mermaid(
+        GraphFormat::Mermaid,
+        "graph TD\n  %% publish order: a, b, c\n  n0[\"a\"]\n  n1[\"b\"]\n  n2[\"c\"]\n  n1 --> n0\n  n2 --> n0\n  n2 --> n1"
+    )]
+    #[case::tree(GraphFormat::Tree, "c\n  a\n  b\n    a\n\nPublish order: a, b, c")]
+    #[trace]
+    fn render_tests(root: VfsPath, #[case] format: GraphFormat, #[case] expected: &str) {
+        // Arrange
+        let graph = DependencyGraph::open(&root).unwrap();
+
+        // Act
+        let actual = graph.render(format);
+
+        // Assert
+        assert_eq!(expected, actual);
+    }
+
+    #[rstest]
+    fn render_cycle_test(root: VfsPath) {
+        // Arrange
+        write_member(&root, "a", "a", &[("c", "0.1.0")]);
+        let graph = DependencyGraph::open(&root).unwrap();
+
+        // Act
+        let tree = graph.render(GraphFormat::Tree);
+        let dot = graph.render(GraphFormat::Dot);
+
+        // Assert
+        assert_eq!(vec![vec!["a", "b", "c"]], graph.cycles());
+        assert!(graph.publish_order().is_empty());
+        assert_eq!(
+            "a\n  c\n    a (cycle)\n    b\n      a (cycle)\n\nPublish order: none because of dependency cycles\nCycle: a, b, c",
+            tree
+        );
+        assert!(dot.contains("\"a\" -> \"c\" [color=red];"));
+    }
+
+    #[rstest]
+    fn render_shared_dependency_test(root: VfsPath) {
+        // Arrange
+        root.join(CARGO_CONFIG)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(b"[workspace]\nmembers = [\"a\", \"b\", \"c\", \"d\", \"e\"]\n")
+            .unwrap();
+        write_member(&root, "a", "a", &[("e", "0.1.0")]);
+        write_member(&root, "c", "c", &[("a", "0.1.0")]);
+        write_member(&root, "d", "d", &[("b", "0.1.0"), ("c", "0.1.0")]);
+        write_member(&root, "e", "e", &[]);
+        let graph = DependencyGraph::open(&root).unwrap();
+
+        // Act
+        let actual = graph.render(GraphFormat::Tree);
+
+        // Assert
+        assert_eq!(
+            "d\n  b\n    a\n      e\n  c\n    a (see above)\n\nPublish order: e, a, c, b, d",
+            actual
+        );
+    }
+
+    #[fixture]
+    fn root() -> VfsPath {
+        let root = VfsPath::new(MemoryFS::new());
+        root.join(CARGO_CONFIG)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(b"[workspace]\nmembers = [\"a\", \"b\", \"c\"]\n")
+            .unwrap();
+        write_member(&root, "a", "a", &[]);
+        write_member(&root, "b", "b", &[("a", "0.1.0")]);
+        write_member(&root, "c", "c", &[("a", "0.1.0"), ("b", "0.1.0")]);
+        root
+    }
+
+    fn write_member(root: &VfsPath, member: &str, name: &str, deps: &[(&str, &str)]) {
+        let dir = root.join(member).unwrap();
+        dir.create_dir_all().unwrap();
+        let mut content = format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\n");
+        if !deps.is_empty() {
+            content.push_str("\n[dependencies]\n");
+        }
+        for (dep, version) in deps {
+            let _ = writeln!(
+                content,
+                "{dep} = {{ path = \"../{dep}\", version = \"{version}\" }}"
+            );
+        }
+        dir.join(CARGO_CONFIG)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(content.as_bytes())
+            .unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use color_eyre::eyre::Result;
+use crate::Result;
 use sha2::{Digest, Sha256};
 use std::io::{BufReader, Read};
 use vfs::VfsPath;
@@ -6,6 +6,7 @@
 extern crate handlebars;
 
 use std::collections::HashMap;
+use std::fmt;
 
 use clap::ValueEnum;
 #[cfg(test)]
@@ -13,46 +14,176 @@ use mockall::{automock, predicate::str};
 use semver::{BuildMetadata, Prerelease, Version};
 use serde::Deserialize;
 
-use color_eyre::eyre::Result;
 use toml_edit::{DocumentMut, value};
 use vfs::VfsPath;
 
 pub mod brew;
 pub mod cargo;
+mod error;
 pub mod git;
+pub mod graph;
 pub mod hash;
+#[cfg(feature = "native-git")]
+pub mod native_git;
+pub mod observer;
 mod packaging;
+pub mod registry;
 mod resource;
 pub mod scoop;
+pub mod status;
 mod version_iter;
 pub mod workflow;
 
+pub use error::{Error, PublishError, Result};
+use scoop::ScoopConfig;
+
 #[cfg(test)] // <-- not needed in integration tests
 extern crate rstest;
 
 const CARGO_CONFIG: &str = "Cargo.toml";
+const CARGO_LOCK: &str = "Cargo.lock";
 const VERSION: &str = "version";
 const PACK: &str = "package";
 const DEPS: &str = "dependencies";
+const DEFAULT_REMOTE: &str = "origin";
+/// Prefix of GitHub repository URLs which releases are checked for new versions
+const GITHUB: &str = "https://github.com/";
 
-#[derive(Default, Eq, PartialEq, Debug)]
+#[derive(Default, Eq, PartialEq, Debug, Clone, Copy)]
 pub struct PublishOptions<'a> {
     pub crate_to_publish: Option<&'a str>,
+    /// Registry name to publish to. Default registry (crates.io) used if not set
+    pub registry: Option<&'a str>,
     pub all_features: bool,
     pub no_verify: bool,
+    /// Features to activate i.e. --features option
+    pub features: &'a [String],
+    pub no_default_features: bool,
+    /// Target triple to build package for i.e. --target option
+    pub target: Option<&'a str>,
+    /// Whether to require Cargo.lock to be up to date i.e. --locked flag
+    pub locked: bool,
+    /// Number of parallel build jobs i.e. --jobs option
+    pub jobs: Option<u16>,
+    /// Whether to allow publishing with uncommitted changes i.e. --allow-dirty flag
+    pub allow_dirty: bool,
 }
 
 #[cfg_attr(test, automock)]
 pub trait Publisher {
     fn publish<'a>(&'a self, path: &'a str, options: PublishOptions<'a>) -> Result<()>;
+    /// Packages and verifies crates specified without publishing. Crates packaged all together
+    /// so that their dependencies on each other resolved even if they aren't published yet.
+    /// Crate located at path packaged if crates list is empty
+    fn package<'a>(
+        &'a self,
+        path: &'a str,
+        crates: &'a [String],
+        options: PublishOptions<'a>,
+    ) -> Result<()>;
+}
+
+/// Registry index that knows which crate versions are published
+#[cfg_attr(test, automock)]
+pub trait Index {
+    /// Whether crate's version is available in the index of the registry specified.
+    /// Default registry (crates.io) used if registry isn't set
+    fn contains<'a>(
+        &'a self,
+        registry: Option<&'a str>,
+        name: &'a str,
+        version: &'a str,
+    ) -> Result<bool>;
+    /// Whether index knows which versions are published at all. There is no point
+    /// in waiting for published crate to appear in index that cannot be queried
+    fn is_queryable(&self) -> bool {
+        true
+    }
+}
+
+/// Options of a commit made by releaser
+#[derive(Default, Eq, PartialEq, Debug)]
+pub struct CommitOptions<'a> {
+    pub message: &'a str,
+    /// Files (relative to repository path) to commit. Only these files staged and committed
+    pub files: &'a [String],
+    /// Whether to sign commit (GPG or SSH key configured in git)
+    pub sign: bool,
+}
+
+/// Options of a release tag
+#[derive(Default, Eq, PartialEq, Debug)]
+pub struct TagOptions<'a> {
+    /// Annotation message. If not set and signing not requested lightweight tag created
+    pub message: Option<&'a str>,
+    /// Whether to sign tag (GPG or SSH key configured in git). Signed tag is always annotated
+    pub sign: bool,
+}
+
+/// Options of pushing changes into remote repository
+#[derive(Eq, PartialEq, Debug)]
+pub struct PushOptions<'a> {
+    /// Remote name to push to
+    pub remote: &'a str,
+    /// Target branch to push current HEAD into. Current branch pushed if not set
+    pub branch: Option<&'a str>,
+}
+
+impl Default for PushOptions<'_> {
+    fn default() -> Self {
+        Self {
+            remote: DEFAULT_REMOTE,
+            branch: None,
+        }
+    }
+}
+
+/// Optional version control features that not every backend supports
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum VcsFeature {
+    /// Signed commits
+    SignCommit,
+    /// Signed tags
+    SignTag,
+    /// Pushing branch and tag together atomically
+    AtomicPush,
+}
+
+impl fmt::Display for VcsFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SignCommit => write!(f, "Commit signing"),
+            Self::SignTag => write!(f, "Tag signing"),
+            Self::AtomicPush => write!(f, "Atomic push"),
+        }
+    }
 }
 
 #[cfg_attr(test, automock)]
 pub trait Vcs {
-    fn commit(&self, path: &str, message: &str) -> Result<()>;
-    fn create_tag(&self, path: &str, tag: &str) -> Result<()>;
-    fn push_tag(&self, path: &str, tag: &str) -> Result<()>;
-    fn push(&self, path: &str) -> Result<()>;
+    fn commit<'a>(&'a self, path: &'a str, options: CommitOptions<'a>) -> Result<()>;
+    fn create_tag<'a>(&'a self, path: &'a str, tag: &'a str, options: TagOptions<'a>)
+    -> Result<()>;
+    fn push_tag<'a>(&'a self, path: &'a str, tag: &'a str, options: PushOptions<'a>) -> Result<()>;
+    fn push<'a>(&'a self, path: &'a str, options: PushOptions<'a>) -> Result<()>;
+    /// Pushes branch and tag together so that either both or none of them updated on remote
+    fn push_atomic<'a>(
+        &'a self,
+        path: &'a str,
+        tag: &'a str,
+        options: PushOptions<'a>,
+    ) -> Result<()>;
+    /// Whether working tree has no changes in tracked files
+    fn is_clean(&self, path: &str) -> Result<bool>;
+    /// Most recent tag reachable from HEAD. None if there are no tags
+    fn last_tag(&self, path: &str) -> Result<Option<String>>;
+    /// Number of commits since tag (from the beginning of history if not set)
+    /// that changed files under the path specified
+    fn commits_since<'a>(&'a self, path: &'a str, tag: Option<&'a str>) -> Result<u32>;
+    /// Whether optional feature is supported. Checked before release changes anything
+    fn supports(&self, _feature: VcsFeature) -> bool {
+        true
+    }
 }
 
 /// Represents a publisher that does nothing.
@@ -63,6 +194,34 @@ impl Publisher for NonPublisher {
     fn publish<'a>(&'a self, _path: &'a str, _options: PublishOptions<'a>) -> Result<()> {
         Ok(())
     }
+
+    fn package<'a>(
+        &'a self,
+        _path: &'a str,
+        _crates: &'a [String],
+        _options: PublishOptions<'a>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Represents an index that has no crates.
+#[derive(Default)]
+pub struct NonIndex;
+
+impl Index for NonIndex {
+    fn contains<'a>(
+        &'a self,
+        _registry: Option<&'a str>,
+        _name: &'a str,
+        _version: &'a str,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn is_queryable(&self) -> bool {
+        false
+    }
 }
 
 /// Updates the configurations by aggregating the maximum version from an iterator of crate versions.
@@ -84,7 +243,7 @@ pub fn update_configs<I>(path: &VfsPath, iter: &mut I, incr: Increment) -> Resul
 where
     I: Iterator<Item = CrateVersion>,
 {
-    let result = Version::parse("0.0.0")?;
+    let result = Version::new(0, 0, 0);
 
     let result = iter
         .by_ref()
@@ -134,9 +293,11 @@ pub fn update_config(path: &VfsPath, version: &CrateVersion, incr: Increment) ->
     let mut content = String::new();
     file.read_to_string(&mut content)?;
 
-    let mut doc = content.parse::<DocumentMut>()?;
+    let mut doc = content
+        .parse::<DocumentMut>()
+        .map_err(|e| Error::toml(working_config_path.as_str(), e))?;
 
-    let mut result = Version::parse("0.0.0")?;
+    let mut result = Version::new(0, 0, 0);
 
     for place in &version.places {
         match place {
@@ -160,7 +321,7 @@ pub fn update_config(path: &VfsPath, version: &CrateVersion, incr: Increment) ->
 }
 
 fn increment(v: &str, i: Increment) -> Result<Version> {
-    let mut v = Version::parse(v)?;
+    let mut v = Version::parse(v).map_err(|e| Error::version(v, e))?;
     match i {
         Increment::Major => increment_major(&mut v),
         Increment::Minor => increment_minor(&mut v),
@@ -256,15 +417,71 @@ struct WorkspaceConfig {
     workspace: Workspace,
 }
 
+impl WorkspaceConfig {
+    pub fn open(path: &VfsPath) -> Result<Self> {
+        let mut file = path.open_file()?;
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        let conf: WorkspaceConfig =
+            toml::from_str(&content).map_err(|e| Error::toml(path.as_str(), e))?;
+        Ok(conf)
+    }
+
+    /// Publish settings of the member specified from
+    /// `[workspace.metadata.releaser.members.<member>]` section
+    pub fn member_publish_config(&self, member: &str) -> Option<&MemberPublishConfig> {
+        self.workspace
+            .metadata
+            .as_ref()?
+            .releaser
+            .as_ref()?
+            .members
+            .as_ref()?
+            .get(member)
+    }
+}
+
 #[derive(Deserialize)]
 struct Workspace {
     members: Vec<String>,
+    metadata: Option<WorkspaceMetadata>,
+}
+
+#[derive(Deserialize)]
+struct WorkspaceMetadata {
+    releaser: Option<ReleaserMetadata>,
+}
+
+#[derive(Deserialize)]
+struct ReleaserMetadata {
+    members: Option<HashMap<String, MemberPublishConfig>>,
+}
+
+/// Member's publish settings that override ones set in command line
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct MemberPublishConfig {
+    all_features: Option<bool>,
+    no_verify: Option<bool>,
+    features: Option<Vec<String>>,
+    no_default_features: Option<bool>,
+    target: Option<String>,
+    locked: Option<bool>,
+    jobs: Option<u16>,
+    allow_dirty: Option<bool>,
 }
 
 #[derive(Deserialize, Default)]
 struct CrateConfig {
     package: Package,
     dependencies: Option<HashMap<String, Dependency>>,
+    bin: Option<Vec<BinTarget>>,
+}
+
+/// `[[bin]]` target
+#[derive(Deserialize, Default)]
+struct BinTarget {
+    name: Option<String>,
 }
 
 impl CrateConfig {
@@ -272,10 +489,28 @@ impl CrateConfig {
         let mut file = path.open_file()?;
         let mut content = String::new();
         file.read_to_string(&mut content)?;
-        let conf: CrateConfig = toml::from_str(&content)?;
+        let conf: CrateConfig =
+            toml::from_str(&content).map_err(|e| Error::toml(path.as_str(), e))?;
         Ok(conf)
     }
 
+    /// Names of binaries crate builds. Binary named after package
+    /// (built from src/main.rs) is assumed if there are no `[[bin]]` targets.
+    /// Binary named after package goes first if any
+    pub fn binaries(&self) -> Vec<String> {
+        let mut binaries = self
+            .bin
+            .iter()
+            .flatten()
+            .filter_map(|b| b.name.clone())
+            .collect::<Vec<String>>();
+        if binaries.is_empty() {
+            binaries.push(self.package.name.clone());
+        }
+        binaries.sort_by_key(|b| *b != self.package.name);
+        binaries
+    }
+
     pub fn new_version(&self, path: String) -> CrateVersion {
         let places = vec![Place::Package(self.package.version.clone())];
 
@@ -290,6 +525,75 @@ struct Package {
     description: Option<String>,
     license: Option<String>,
     homepage: Option<String>,
+    repository: Option<String>,
+    publish: Option<Publish>,
+    metadata: Option<PackageMetadata>,
+}
+
+impl Package {
+    /// Where the crate is published to considering registry requested and `publish` manifest setting
+    fn destination<'a>(&'a self, registry: Option<&'a str>) -> Destination<'a> {
+        match &self.publish {
+            None | Some(Publish::Flag(true)) => Destination::Registry(registry),
+            Some(Publish::Flag(false)) => Destination::Nowhere,
+            Some(Publish::Registries(allowed)) => match registry {
+                Some(r) if allowed.iter().any(|a| a == r) => Destination::Registry(Some(r)),
+                Some(_) => Destination::Nowhere,
+                None => allowed
+                    .first()
+                    .map_or(Destination::Nowhere, |r| Destination::Registry(Some(r))),
+            },
+        }
+    }
+
+    /// Homebrew formula settings from `[package.metadata.releaser.brew]` section
+    fn brew_config(&self) -> Option<&BrewConfig> {
+        self.metadata.as_ref()?.releaser.as_ref()?.brew.as_ref()
+    }
+
+    fn scoop_config(&self) -> Option<&ScoopConfig> {
+        self.metadata.as_ref()?.releaser.as_ref()?.scoop.as_ref()
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct PackageMetadata {
+    releaser: Option<PackageReleaserMetadata>,
+}
+
+#[derive(Deserialize, Default)]
+struct PackageReleaserMetadata {
+    brew: Option<BrewConfig>,
+    scoop: Option<ScoopConfig>,
+}
+
+/// Homebrew formula settings that complement ones set in command line
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct BrewConfig {
+    /// Handlebars formula template path relative to crate's root
+    template: Option<String>,
+    /// Handlebars cask template path relative to crate's root
+    cask_template: Option<String>,
+    /// Identifier of package installed by cask's pkg installer
+    pkg_id: Option<String>,
+}
+
+/// `publish` manifest setting
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Publish {
+    Flag(bool),
+    Registries(Vec<String>),
+}
+
+/// Where crate is published to
+#[derive(Debug, PartialEq, Eq)]
+enum Destination<'a> {
+    /// Registry name to publish to. Default registry (crates.io) used if not set
+    Registry(Option<&'a str>),
+    /// Crate must not be published
+    Nowhere,
 }
 
 #[derive(Deserialize, Debug)]
@@ -318,6 +622,17 @@ pub enum Place {
     Dependency(String, String),
 }
 
+/// Version control backend used to commit, tag and push release changes
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum VcsBackend {
+    /// Runs git executable
+    #[default]
+    Git,
+    /// Uses built-in libgit2 so git executable isn't required
+    #[cfg(feature = "native-git")]
+    Native,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum Increment {
     Major,
@@ -347,6 +662,38 @@ mod tests {
         assert_eq!(actual, Version::parse(expected).unwrap());
     }
 
+    #[test]
+    fn increment_invalid_version() {
+        // Arrange
+        let v = "0.1";
+
+        // Act
+        let actual = increment(v, Increment::Patch);
+
+        // Assert
+        assert_eq!("Invalid version '0.1'", actual.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn open_invalid_toml() {
+        // Arrange
+        let root = VfsPath::new(vfs::MemoryFS::new());
+        let path = root.join(CARGO_CONFIG).unwrap();
+        path.create_file()
+            .unwrap()
+            .write_all(b"[package\n")
+            .unwrap();
+
+        // Act
+        let actual = CrateConfig::open(&path);
+
+        // Assert
+        assert_eq!(
+            "Invalid TOML in /Cargo.toml",
+            actual.err().unwrap().to_string()
+        );
+    }
+
     #[test]
     fn toml_parse_workspace() {
         // Arrange
@@ -358,6 +705,25 @@ mod tests {
         assert_eq!(2, cfg.workspace.members.len());
     }
 
+    #[test]
+    fn toml_parse_workspace_members_metadata() {
+        // Arrange
+        let conf = format!(
+            "{WKS}\n[workspace.metadata.releaser.members.solv]\nfeatures = [\"a\", \"b\"]\nno-default-features = true\njobs = 2\n"
+        );
+
+        // Act
+        let cfg: WorkspaceConfig = toml::from_str(&conf).unwrap();
+
+        // Assert
+        let solv = cfg.member_publish_config("solv").unwrap();
+        assert_eq!(Some(vec!["a".to_string(), "b".to_string()]), solv.features);
+        assert_eq!(Some(true), solv.no_default_features);
+        assert_eq!(Some(2), solv.jobs);
+        assert_eq!(None, solv.locked);
+        assert!(cfg.member_publish_config("solp").is_none());
+    }
+
     #[test]
     fn toml_parse_crate() {
         // Arrange
@@ -439,6 +805,53 @@ lto = true"#;
         }
     }
 
+    #[rstest]
+    #[case::not_set("", None, Destination::Registry(None))]
+    #[case::not_set_registry("", Some("my"), Destination::Registry(Some("my")))]
+    #[case::enabled("publish = true", None, Destination::Registry(None))]
+    #[case::disabled("publish = false", None, Destination::Nowhere)]
+    #[case::disabled_registry("publish = false", Some("my"), Destination::Nowhere)]
+    #[case::list("publish = [\"my\"]", None, Destination::Registry(Some("my")))]
+    #[case::list_match(
+        "publish = [\"x\", \"my\"]",
+        Some("my"),
+        Destination::Registry(Some("my"))
+    )]
+    #[case::list_mismatch("publish = [\"x\"]", Some("my"), Destination::Nowhere)]
+    #[case::empty_list("publish = []", None, Destination::Nowhere)]
+    #[trace]
+    fn destination_tests(
+        #[case] publish: &str,
+        #[case] registry: Option<&str>,
+        #[case] expected: Destination,
+    ) {
+        // Arrange
+        let conf = format!("[package]\nname = \"a\"\nversion = \"0.1.0\"\n{publish}\n");
+        let cfg: CrateConfig = toml::from_str(&conf).unwrap();
+
+        // Act
+        let actual = cfg.package.destination(registry);
+
+        // Assert
+        assert_eq!(expected, actual);
+    }
+
+    #[rstest]
+    #[case::no_targets("", vec!["a"])]
+    #[case::targets("[[bin]]\nname = \"x\"\n[[bin]]\nname = \"a\"\n", vec!["a", "x"])]
+    #[trace]
+    fn binaries_tests(#[case] targets: &str, #[case] expected: Vec<&str>) {
+        // Arrange
+        let conf = format!("[package]\nname = \"a\"\nversion = \"0.1.0\"\n{targets}");
+        let cfg: CrateConfig = toml::from_str(&conf).unwrap();
+
+        // Act
+        let actual = cfg.binaries();
+
+        // Assert
+        assert_eq!(expected, actual);
+    }
+
     const WKS: &str = r#"
 [workspace]
 
@@ -10,6 +10,7 @@ use bugreport::{
 use clap::{Arg, ArgAction, ArgMatches, Command, command};
 use clap_complete::{Shell, generate};
 use color_eyre::eyre::{Result, eyre};
+use console::style;
 use std::io;
 use std::option::Option::Some;
 use std::path::PathBuf;
@@ -18,9 +19,17 @@ use vfs::{PhysicalFS, VfsPath};
 use releaser::brew;
 use releaser::cargo::Cargo;
 use releaser::git::Git;
+use releaser::graph::{DependencyGraph, GraphFormat};
+#[cfg(feature = "native-git")]
+use releaser::native_git::NativeGit;
+use releaser::registry::CargoIndex;
 use releaser::scoop;
-use releaser::workflow::{Crate, Release, VPath, Workspace};
-use releaser::{Increment, NonPublisher};
+use releaser::status::{self, Status};
+use releaser::workflow::{
+    Crate, Hooks, PublishSettings, Release, ReleaseOptions, RetrySettings, VPath, VcsOptions,
+    Workspace,
+};
+use releaser::{Increment, NonIndex, NonPublisher, PushOptions, Vcs, VcsBackend};
 
 const PATH: &str = "PATH";
 const FILE: &str = "FILE";
@@ -43,10 +52,58 @@ const CRATE: &str = "crate";
 const BASE_HELP: &str = "Base URI of downloaded artifacts";
 const EXE: &str = "exe";
 const BINARY: &str = "binary";
+const BINARY32: &str = "binary32";
+const BINARYARM: &str = "binaryarm";
+const EXTRACT_DIR: &str = "extractdir";
+const ALIAS: &str = "alias";
+const SHORTCUT: &str = "shortcut";
+const PERSIST: &str = "persist";
+const ENV_PATH: &str = "envpath";
+const DEPENDS: &str = "depends";
+const NOTES: &str = "notes";
 const DELAY: &str = "delay";
 const LINUX: &str = "linux";
+const LINUXARM: &str = "linuxarm";
+const TEMPLATE: &str = "template";
+const FORMULA_NAME: &str = "formula-name";
+const TAP: &str = "tap";
+const CASK: &str = "cask";
+const CASK_TEMPLATE: &str = "casktemplate";
+const APP: &str = "app";
+const PKG_ID: &str = "pkgid";
+const TAP_MESSAGE: &str = "tapmsg";
 const MACOS: &str = "macos";
 const MACOSARM: &str = "macosarm";
+const TEXT: &str = "TEXT";
+const TAG_MESSAGE: &str = "tagmsg";
+const TAG_MESSAGE_FILE: &str = "tagmsgfile";
+const SIGN_TAG: &str = "signtag";
+const SIGN_COMMIT: &str = "signcommit";
+const NAME: &str = "NAME";
+const REMOTE: &str = "remote";
+const BRANCH: &str = "branch";
+const ATOMIC: &str = "atomic";
+const INCLUDE: &str = "include";
+const ALLOW_DIRTY: &str = "allowdirty";
+const ALLOW_DIRTY_TREE: &str = "allowdirtytree";
+const VCS: &str = "vcs";
+const REGISTRY: &str = "registry";
+const FEATURES: &str = "features";
+const NO_DEFAULT_FEATURES: &str = "nodefaultfeatures";
+const TARGET: &str = "target";
+const TRIPLE: &str = "TRIPLE";
+const LOCKED: &str = "locked";
+const JOBS: &str = "jobs";
+const BACKEND: &str = "BACKEND";
+const COMMAND: &str = "COMMAND";
+const RETRIES: &str = "retries";
+const BACKOFF: &str = "backoff";
+const DRY_RUN: &str = "dryrun";
+const NO_PACKAGE: &str = "nopackage";
+const BEFORE_COMMIT: &str = "beforecommit";
+const AFTER_RELEASE: &str = "afterrelease";
+const FORMAT: &str = "format";
+const FORMAT_VALUE: &str = "FORMAT";
 
 fn main() -> Result<()> {
     color_eyre::install()?;
@@ -58,6 +115,8 @@ fn main() -> Result<()> {
         Some(("c", cmd)) => single_crate(cmd),
         Some(("b", cmd)) => brew(cmd),
         Some(("s", cmd)) => scoop(cmd),
+        Some(("graph", cmd)) => graph(cmd),
+        Some(("status", cmd)) => release_status(cmd),
         Some(("completion", cmd)) => {
             print_completions(cmd);
             Ok(())
@@ -88,51 +147,140 @@ fn print_bugreport(_matches: &ArgMatches) {
 }
 
 fn workspace(cmd: &ArgMatches) -> Result<()> {
-    let delay_seconds = cmd.get_one::<u64>(DELAY).unwrap_or(&20);
+    match vcs_backend(cmd) {
+        VcsBackend::Git => workspace_with(cmd, Git),
+        #[cfg(feature = "native-git")]
+        VcsBackend::Native => workspace_with(cmd, NativeGit),
+    }
+}
+
+fn workspace_with<V: Vcs>(cmd: &ArgMatches, vcs: V) -> Result<()> {
     if cmd.get_flag(NO_PUBLISH) {
-        let r = Workspace::new(*delay_seconds, NonPublisher, Git);
+        let r = Workspace::new(NonPublisher, vcs, NonIndex);
         release(cmd, &r)
     } else {
-        let r = Workspace::new(*delay_seconds, Cargo, Git);
+        let index = CargoIndex::new(cmd.get_one::<String>(PATH).unwrap());
+        let r = Workspace::new(Cargo, vcs, index);
         release(cmd, &r)
     }
 }
 
 fn single_crate(cmd: &ArgMatches) -> Result<()> {
+    match vcs_backend(cmd) {
+        VcsBackend::Git => single_crate_with(cmd, Git),
+        #[cfg(feature = "native-git")]
+        VcsBackend::Native => single_crate_with(cmd, NativeGit),
+    }
+}
+
+fn single_crate_with<V: Vcs>(cmd: &ArgMatches, vcs: V) -> Result<()> {
     if cmd.get_flag(NO_PUBLISH) {
-        let r = Crate::new(NonPublisher, Git);
+        let r = Crate::new(NonPublisher, vcs, NonIndex);
         release(cmd, &r)
     } else {
-        let r = Crate::new(Cargo, Git);
+        let index = CargoIndex::new(cmd.get_one::<String>(PATH).unwrap());
+        let r = Crate::new(Cargo, vcs, index);
         release(cmd, &r)
     }
 }
 
+fn vcs_backend(cmd: &ArgMatches) -> VcsBackend {
+    cmd.get_one::<VcsBackend>(VCS).copied().unwrap_or_default()
+}
+
 fn brew(cmd: &ArgMatches) -> Result<()> {
     let empty = String::default();
     let linux_path = cmd.get_one::<String>(LINUX).unwrap_or(&empty);
+    let linux_arm_path = cmd.get_one::<String>(LINUXARM).unwrap_or(&empty);
     let macos_path = cmd.get_one::<String>(MACOS).unwrap_or(&empty);
     let macos_arm_path = cmd.get_one::<String>(MACOSARM).unwrap_or(&empty);
 
-    if linux_path.is_empty() && macos_path.is_empty() {
+    if [linux_path, linux_arm_path, macos_path, macos_arm_path]
+        .iter()
+        .all(|p| p.is_empty())
+    {
         return Ok(());
     }
 
     let crate_path = cmd.get_one::<String>(CRATE).unwrap_or(&empty);
     let base_uri = cmd.get_one::<String>(BASE).unwrap_or(&empty);
+    let template = cmd
+        .get_one::<String>(TEMPLATE)
+        .map(std::fs::read_to_string)
+        .transpose()?;
+    let cask_template = cmd
+        .get_one::<String>(CASK_TEMPLATE)
+        .map(std::fs::read_to_string)
+        .transpose()?;
+    let formula_name = cmd.get_one::<String>(FORMULA_NAME);
 
     let crate_path: VfsPath = PhysicalFS::new(PathBuf::from(crate_path)).into();
     let linux_path: VfsPath = PhysicalFS::new(PathBuf::from(linux_path)).into();
+    let linux_arm_path: VfsPath = PhysicalFS::new(PathBuf::from(linux_arm_path)).into();
     let macos_path: VfsPath = PhysicalFS::new(PathBuf::from(macos_path)).into();
     let macos_arm_path: VfsPath = PhysicalFS::new(PathBuf::from(macos_arm_path)).into();
-    let b = brew::Brew::serialize(
-        &crate_path,
-        &linux_path,
-        &macos_path,
-        &macos_arm_path,
+    let options = brew::BrewOptions {
         base_uri,
-    )?;
-    output_string(cmd, b)
+        template: template.as_deref(),
+        cask_template: cask_template.as_deref(),
+        formula_name: formula_name.map(String::as_str),
+        pkg_id: cmd.get_one::<String>(PKG_ID).map(String::as_str),
+    };
+    let b = if cmd.get_flag(CASK) {
+        let apps = cmd
+            .get_many::<String>(APP)
+            .unwrap_or_default()
+            .cloned()
+            .collect::<Vec<_>>();
+        brew::Cask::serialize(&crate_path, &macos_path, &macos_arm_path, &apps, options)?
+    } else {
+        brew::Brew::serialize(
+            &crate_path,
+            &linux_path,
+            &linux_arm_path,
+            &macos_path,
+            &macos_arm_path,
+            options,
+        )?
+    };
+    for warning in &b.warnings {
+        eprintln!("{} {warning}", style("warning:").yellow().bold());
+    }
+    match cmd.get_one::<String>(TAP) {
+        Some(tap) => match vcs_backend(cmd) {
+            VcsBackend::Git => update_tap(cmd, Git, tap, &crate_path, &b.content),
+            #[cfg(feature = "native-git")]
+            VcsBackend::Native => update_tap(cmd, NativeGit, tap, &crate_path, &b.content),
+        },
+        None => output_string(cmd, b.content),
+    }
+}
+
+fn update_tap<V: Vcs>(
+    cmd: &ArgMatches,
+    vcs: V,
+    path: &str,
+    crate_path: &VfsPath,
+    formula: &str,
+) -> Result<()> {
+    let t: VfsPath = PhysicalFS::new(PathBuf::from(path)).into();
+    let tap = VPath::new(path, t);
+    let options = brew::TapOptions {
+        formula_name: cmd.get_one::<String>(FORMULA_NAME).map(String::as_str),
+        message: cmd.get_one::<String>(TAP_MESSAGE).map(String::as_str),
+        sign: cmd.get_flag(SIGN_COMMIT),
+        cask: cmd.get_flag(CASK),
+        push: PushOptions {
+            remote: cmd.get_one::<String>(REMOTE).unwrap(),
+            branch: cmd.get_one::<String>(BRANCH).map(String::as_str),
+        },
+    };
+    if brew::Tap::new(vcs).update(&tap, crate_path, formula, options)? {
+        println!("Package definition published into tap {path}");
+    } else {
+        println!("Package definition in tap {path} is up to date");
+    }
+    Ok(())
 }
 
 fn scoop(cmd: &ArgMatches) -> Result<()> {
@@ -144,11 +292,73 @@ fn scoop(cmd: &ArgMatches) -> Result<()> {
 
     let crate_path: VfsPath = PhysicalFS::new(PathBuf::from(crate_path)).into();
     let binary_path: VfsPath = PhysicalFS::new(PathBuf::from(binary_path)).into();
-
-    let scoop = scoop::Scoop::serialize(&crate_path, &binary_path, exe_name, base_uri)?;
+    let optional_path = |id: &str| -> Option<VfsPath> {
+        let path = cmd.get_one::<String>(id)?;
+        Some(PhysicalFS::new(PathBuf::from(path)).into())
+    };
+    let binary32_path = optional_path(BINARY32);
+    let binary_arm_path = optional_path(BINARYARM);
+
+    let scoop = scoop::Scoop::serialize(
+        &crate_path,
+        &binary_path,
+        binary32_path.as_ref(),
+        binary_arm_path.as_ref(),
+        exe_name,
+        base_uri,
+        scoop_config(cmd),
+    )?;
     output_string(cmd, scoop)
 }
 
+/// Scoop manifest settings set in command line
+fn scoop_config(cmd: &ArgMatches) -> scoop::ScoopConfig {
+    let many = |id: &str| {
+        cmd.get_many::<String>(id)
+            .map(|values| values.cloned().collect())
+    };
+    scoop::ScoopConfig {
+        extract_dir: cmd.get_one::<String>(EXTRACT_DIR).cloned(),
+        alias: cmd.get_one::<String>(ALIAS).cloned(),
+        shortcut: cmd.get_one::<String>(SHORTCUT).cloned(),
+        persist: many(PERSIST),
+        env_add_path: many(ENV_PATH),
+        depends: many(DEPENDS),
+        notes: many(NOTES),
+    }
+}
+
+fn graph(cmd: &ArgMatches) -> Result<()> {
+    let path = cmd.get_one::<String>(PATH).unwrap();
+    let format = cmd
+        .get_one::<GraphFormat>(FORMAT)
+        .copied()
+        .unwrap_or_default();
+
+    let root: VfsPath = PhysicalFS::new(PathBuf::from(path)).into();
+    let graph = DependencyGraph::open(&root)?;
+    output_string(cmd, graph.render(format))
+}
+
+fn release_status(cmd: &ArgMatches) -> Result<()> {
+    match vcs_backend(cmd) {
+        VcsBackend::Git => release_status_with(cmd, Git),
+        #[cfg(feature = "native-git")]
+        VcsBackend::Native => release_status_with(cmd, NativeGit),
+    }
+}
+
+fn release_status_with<V: Vcs>(cmd: &ArgMatches, vcs: V) -> Result<()> {
+    let path = cmd.get_one::<String>(PATH).unwrap();
+    let registry = cmd.get_one::<String>(REGISTRY).map(String::as_str);
+
+    let r: VfsPath = PhysicalFS::new(PathBuf::from(path)).into();
+    let root = VPath::new(path, r);
+    let status = Status::new(vcs, CargoIndex::new(path));
+    let statuses = status.workspace(&root, registry)?;
+    output_string(cmd, status::render(&statuses))
+}
+
 /// Helper function that outputs string specified into
 /// console or file that set by command line option
 fn output_string(cmd: &ArgMatches, s: String) -> Result<()> {
@@ -172,16 +382,83 @@ where
 {
     let path = cmd.get_one::<String>(PATH).unwrap();
     let incr = cmd.get_one::<Increment>(INCR);
-    let all_features = cmd.get_flag(ALL);
-    let no_verify = cmd.get_flag(NO_VERIFY);
 
     if incr.is_none() {
         return Ok(());
     }
 
+    let publish = PublishSettings {
+        registry: cmd.get_one::<String>(REGISTRY).cloned(),
+        all_features: cmd.get_flag(ALL),
+        no_verify: cmd.get_flag(NO_VERIFY),
+        features: cmd
+            .get_many::<String>(FEATURES)
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        no_default_features: cmd.get_flag(NO_DEFAULT_FEATURES),
+        target: cmd.get_one::<String>(TARGET).cloned(),
+        locked: cmd.get_flag(LOCKED),
+        jobs: cmd.get_one::<u16>(JOBS).copied(),
+        allow_dirty: cmd.get_flag(ALLOW_DIRTY),
+    };
+
+    let tag_message = if let Some(file) = cmd.get_one::<String>(TAG_MESSAGE_FILE) {
+        Some(std::fs::read_to_string(file)?)
+    } else {
+        cmd.get_one::<String>(TAG_MESSAGE).cloned()
+    };
+    let vcs_options = VcsOptions {
+        tag_message,
+        sign_tag: cmd.get_flag(SIGN_TAG),
+        sign_commit: cmd.get_flag(SIGN_COMMIT),
+        remote: cmd.get_one::<String>(REMOTE).unwrap().clone(),
+        branch: cmd.get_one::<String>(BRANCH).cloned(),
+        atomic: cmd.get_flag(ATOMIC),
+        files: cmd
+            .get_many::<String>(INCLUDE)
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        allow_dirty_tree: cmd.get_flag(ALLOW_DIRTY_TREE),
+    };
+
+    let hooks = Hooks {
+        before_commit: cmd
+            .get_many::<String>(BEFORE_COMMIT)
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        after_release: cmd
+            .get_many::<String>(AFTER_RELEASE)
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+    };
+
+    let options = ReleaseOptions::builder(*incr.unwrap())
+        .publish(publish)
+        .vcs(vcs_options)
+        // delay makes sense only for workspaces so crate command has no such option
+        .delay_seconds(
+            cmd.try_get_one::<u64>(DELAY)
+                .ok()
+                .flatten()
+                .copied()
+                .unwrap_or_default(),
+        )
+        .dry_run(cmd.get_flag(DRY_RUN))
+        .verify_packages(!cmd.get_flag(NO_PACKAGE))
+        .hooks(hooks)
+        .retry(RetrySettings {
+            retries: *cmd.get_one::<u32>(RETRIES).unwrap(),
+            backoff_seconds: *cmd.get_one::<u64>(BACKOFF).unwrap(),
+        })
+        .build();
+
     let r: VfsPath = PhysicalFS::new(PathBuf::from(path)).into();
     let root = VPath::new(path, r);
-    release.release(root, *incr.unwrap(), all_features, no_verify)
+    Ok(release.release(root, &options)?)
 }
 
 fn build_cli() -> Command {
@@ -195,6 +472,8 @@ fn build_cli() -> Command {
         .subcommand(crate_cmd())
         .subcommand(brew_cmd())
         .subcommand(scoop_cmd())
+        .subcommand(graph_cmd())
+        .subcommand(status_cmd())
         .subcommand(completion_cmd())
         .subcommand(bugreport_cmd())
 }
@@ -218,11 +497,15 @@ fn workspace_cmd() -> Command {
                 .required(false)
                 .value_parser(value_parser!(u64))
                 .default_value("20")
-                .help("Delay in seconds between publish next workflow's crate"),
+                .help("Maximum time in seconds to wait for published crate to appear in registry index before publishing next one"),
         )
         .arg(all_arg())
         .arg(noverify_arg())
         .arg(nopublish_arg())
+        .arg(registry_arg())
+        .args(publish_args())
+        .args(vcs_args())
+        .args(release_args())
 }
 
 fn crate_cmd() -> Command {
@@ -239,6 +522,10 @@ fn crate_cmd() -> Command {
         .arg(all_arg())
         .arg(noverify_arg())
         .arg(nopublish_arg())
+        .arg(registry_arg())
+        .args(publish_args())
+        .args(vcs_args())
+        .args(release_args())
 }
 
 fn brew_cmd() -> Command {
@@ -252,7 +539,14 @@ fn brew_cmd() -> Command {
                 .short('l')
                 .value_name(PATH)
                 .required(false)
-                .help("Sets Linux package directory path"),
+                .help("Sets Linux x64-86 package directory path"),
+        )
+        .arg(
+            Arg::new(LINUXARM)
+                .long(LINUXARM)
+                .value_name(PATH)
+                .required(false)
+                .help("Sets Linux ARM64 package directory path"),
         )
         .arg(
             Arg::new(MACOS)
@@ -270,6 +564,80 @@ fn brew_cmd() -> Command {
                 .required(false)
                 .help("Sets Mac OS ARM64 package directory path"),
         )
+        .arg(
+            Arg::new(TEMPLATE)
+                .long(TEMPLATE)
+                .value_name(FILE)
+                .required(false)
+                .conflicts_with(CASK)
+                .help("Handlebars template to render formula with instead of the built-in one. Overrides template set in crate's manifest"),
+        )
+        .arg(
+            Arg::new(FORMULA_NAME)
+                .long(FORMULA_NAME)
+                .value_name(NAME)
+                .required(false)
+                .help("Formula name to derive formula class name from (cask token in cask mode). Crate's name used if not set"),
+        )
+        .arg(
+            Arg::new(CASK)
+                .long(CASK)
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .help("Create Cask of macOS dmg, pkg or zip packages instead of Formula. Linux packages are ignored"),
+        )
+        .arg(
+            Arg::new(CASK_TEMPLATE)
+                .long(CASK_TEMPLATE)
+                .value_name(FILE)
+                .required(false)
+                .requires(CASK)
+                .help("Handlebars template to render cask with instead of the built-in one. Overrides cask template set in crate's manifest"),
+        )
+        .arg(
+            Arg::new(APP)
+                .long(APP)
+                .value_name(NAME)
+                .required(false)
+                .requires(CASK)
+                .action(ArgAction::Append)
+                .help("App bundle (for example MyApp.app) cask moves into Applications directory. Can be set several times"),
+        )
+        .arg(
+            Arg::new(PKG_ID)
+                .long(PKG_ID)
+                .value_name(NAME)
+                .required(false)
+                .requires(CASK)
+                .help("Identifier of package installed by cask's pkg installer. Used to uninstall it. Overrides identifier set in crate's manifest"),
+        )
+        .arg(
+            Arg::new(TAP)
+                .long(TAP)
+                .value_name(PATH)
+                .required(false)
+                .help("Homebrew tap repository checkout path. Formula written into its Formula directory, committed and pushed instead of being output"),
+        )
+        .arg(
+            Arg::new(TAP_MESSAGE)
+                .long(TAP_MESSAGE)
+                .value_name(TEXT)
+                .required(false)
+                .requires(TAP)
+                .default_value(brew::DEFAULT_TAP_MESSAGE)
+                .help("Tap commit message. {name} and {version} are replaced by formula name and crate's version"),
+        )
+        .arg(
+            Arg::new(SIGN_COMMIT)
+                .long(SIGN_COMMIT)
+                .required(false)
+                .requires(TAP)
+                .action(ArgAction::SetTrue)
+                .help("Whether to sign tap commit using GPG or SSH key configured in git"),
+        )
+        .arg(remote_arg().requires(TAP).help("Git remote to push tap changes to"))
+        .arg(branch_arg().requires(TAP))
+        .arg(vcs_arg())
         .arg(base_arg())
         .arg(output_arg())
 }
@@ -287,6 +655,20 @@ fn scoop_cmd() -> Command {
                 .required(true)
                 .help("Sets 64-bit binary package directory path"),
         )
+        .arg(
+            Arg::new(BINARY32)
+                .long(BINARY32)
+                .value_name(PATH)
+                .required(false)
+                .help("Sets 32-bit binary package directory path"),
+        )
+        .arg(
+            Arg::new(BINARYARM)
+                .long(BINARYARM)
+                .value_name(PATH)
+                .required(false)
+                .help("Sets ARM64 binary package directory path"),
+        )
         .arg(
             Arg::new(EXE)
                 .long(EXE)
@@ -295,10 +677,106 @@ fn scoop_cmd() -> Command {
                 .required(true)
                 .help("Sets Windows executable name"),
         )
+        .arg(
+            Arg::new(EXTRACT_DIR)
+                .long(EXTRACT_DIR)
+                .value_name(PATH)
+                .required(false)
+                .help("Package archive directory to extract. Archive's top level directory used if not set"),
+        )
+        .arg(
+            Arg::new(ALIAS)
+                .long(ALIAS)
+                .value_name(NAME)
+                .required(false)
+                .help("Name executable is available by in command line instead of its file name"),
+        )
+        .arg(
+            Arg::new(SHORTCUT)
+                .long(SHORTCUT)
+                .value_name(NAME)
+                .required(false)
+                .help("Start menu shortcut name of executable"),
+        )
+        .arg(
+            Arg::new(PERSIST)
+                .long(PERSIST)
+                .value_name(PATH)
+                .required(false)
+                .action(ArgAction::Append)
+                .help("File or directory kept between updates. Can be set several times"),
+        )
+        .arg(
+            Arg::new(ENV_PATH)
+                .long(ENV_PATH)
+                .value_name(PATH)
+                .required(false)
+                .action(ArgAction::Append)
+                .help("Directory (relative to app directory) added to PATH. Can be set several times"),
+        )
+        .arg(
+            Arg::new(DEPENDS)
+                .long(DEPENDS)
+                .value_name(NAME)
+                .required(false)
+                .action(ArgAction::Append)
+                .help("App installed before this one. Can be set several times"),
+        )
+        .arg(
+            Arg::new(NOTES)
+                .long(NOTES)
+                .value_name(TEXT)
+                .required(false)
+                .action(ArgAction::Append)
+                .help("Message line shown after installation. Can be set several times"),
+        )
         .arg(base_arg())
         .arg(output_arg())
 }
 
+fn graph_cmd() -> Command {
+    Command::new("graph")
+        .about("Print workspace members dependency graph, publish order and dependency cycles")
+        .arg(
+            Arg::new(PATH)
+                .help("Sets workspace root path")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::new(FORMAT)
+                .long(FORMAT)
+                .short('f')
+                .value_name(FORMAT_VALUE)
+                .value_parser(value_parser!(GraphFormat))
+                .required(false)
+                .default_value("tree")
+                .help("Graph output format"),
+        )
+        .arg(output_arg())
+}
+
+fn status_cmd() -> Command {
+    Command::new("status")
+        .about("Show release state of workspace members: version, last release tag, commits since it and whether version is published")
+        .arg(
+            Arg::new(PATH)
+                .help("Sets workspace root path")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::new(REGISTRY)
+                .long(REGISTRY)
+                .short('r')
+                .value_name(NAME)
+                .required(false)
+                .help("Registry to check crates publishable to and published in. Default registry (crates.io) used if not set"),
+        )
+        .arg(vcs_arg())
+        .arg(output_arg())
+}
+
 fn completion_cmd() -> Command {
     Command::new("completion")
         .about("Generate the autocompletion script for the specified shell")
@@ -360,6 +838,96 @@ fn noverify_arg() -> Arg {
         .help(NO_VERIFY_HELP)
 }
 
+fn publish_args() -> [Arg; 8] {
+    [
+        Arg::new(FEATURES)
+            .long(FEATURES)
+            .short('F')
+            .value_name("LIST")
+            .required(false)
+            .value_delimiter(',')
+            .action(ArgAction::Append)
+            .help("Comma separated list of features to activate i.e. --features option of cargo publish"),
+        Arg::new(NO_DEFAULT_FEATURES)
+            .long(NO_DEFAULT_FEATURES)
+            .required(false)
+            .action(ArgAction::SetTrue)
+            .help("Whether to add option --no-default-features to cargo publish command"),
+        Arg::new(TARGET)
+            .long(TARGET)
+            .value_name(TRIPLE)
+            .required(false)
+            .help("Target triple to build package for i.e. --target option of cargo publish"),
+        Arg::new(LOCKED)
+            .long(LOCKED)
+            .required(false)
+            .action(ArgAction::SetTrue)
+            .help("Whether to add option --locked to cargo publish command"),
+        Arg::new(JOBS)
+            .long(JOBS)
+            .short('j')
+            .value_name(NUMBER)
+            .required(false)
+            .value_parser(value_parser!(u16))
+            .help("Number of parallel jobs i.e. --jobs option of cargo publish"),
+        Arg::new(ALLOW_DIRTY)
+            .long(ALLOW_DIRTY)
+            .required(false)
+            .action(ArgAction::SetTrue)
+            .help("Whether to add option --allow-dirty to cargo publish command"),
+        Arg::new(RETRIES)
+            .long(RETRIES)
+            .value_name(NUMBER)
+            .required(false)
+            .value_parser(value_parser!(u32))
+            .default_value("3")
+            .help("Max number of publish retries on transient failures (registry rate limit, network or registry server errors)"),
+        Arg::new(BACKOFF)
+            .long(BACKOFF)
+            .value_name(NUMBER)
+            .required(false)
+            .value_parser(value_parser!(u64))
+            .default_value("10")
+            .help("Delay in seconds before the first publish retry. Doubled before every next retry"),
+    ]
+}
+
+fn release_args() -> [Arg; 4] {
+    [
+        Arg::new(DRY_RUN)
+            .long(DRY_RUN)
+            .required(false)
+            .action(ArgAction::SetTrue)
+            .help("Show what would be done without changing any file, committing, publishing or pushing"),
+        Arg::new(NO_PACKAGE)
+            .long(NO_PACKAGE)
+            .required(false)
+            .action(ArgAction::SetTrue)
+            .help("Skip packaging verification of all crates (cargo package) before version change commit"),
+        Arg::new(BEFORE_COMMIT)
+            .long(BEFORE_COMMIT)
+            .value_name(COMMAND)
+            .required(false)
+            .action(ArgAction::Append)
+            .help("Shell command to run after version change but before commit. RELEASER_VERSION environment variable contains new version. Can be set several times"),
+        Arg::new(AFTER_RELEASE)
+            .long(AFTER_RELEASE)
+            .value_name(COMMAND)
+            .required(false)
+            .action(ArgAction::Append)
+            .help("Shell command to run after release tag pushed. RELEASER_VERSION environment variable contains new version. Can be set several times"),
+    ]
+}
+
+fn registry_arg() -> Arg {
+    Arg::new(REGISTRY)
+        .long(REGISTRY)
+        .short('r')
+        .value_name(NAME)
+        .required(false)
+        .help("Registry to publish to i.e. pass --registry option to cargo publish. Crates which publish setting doesn't allow the registry are skipped")
+}
+
 fn nopublish_arg() -> Arg {
     Arg::new(NO_PUBLISH)
         .long(NO_PUBLISH)
@@ -376,3 +944,74 @@ fn all_arg() -> Arg {
         .action(ArgAction::SetTrue)
         .help(ALL_HELP)
 }
+
+fn vcs_args() -> [Arg; 10] {
+    [
+        Arg::new(TAG_MESSAGE)
+            .long(TAG_MESSAGE)
+            .value_name(TEXT)
+            .required(false)
+            .conflicts_with(TAG_MESSAGE_FILE)
+            .help("Create annotated release tag with the message specified (release notes for example)"),
+        Arg::new(TAG_MESSAGE_FILE)
+            .long(TAG_MESSAGE_FILE)
+            .value_name(FILE)
+            .required(false)
+            .help("Create annotated release tag with the message read from the file specified"),
+        Arg::new(SIGN_TAG)
+            .long(SIGN_TAG)
+            .required(false)
+            .action(ArgAction::SetTrue)
+            .help("Whether to sign release tag using GPG or SSH key configured in git"),
+        Arg::new(SIGN_COMMIT)
+            .long(SIGN_COMMIT)
+            .required(false)
+            .action(ArgAction::SetTrue)
+            .help("Whether to sign version change commit using GPG or SSH key configured in git"),
+        remote_arg().help("Git remote to push changes and release tag to"),
+        branch_arg(),
+        Arg::new(ATOMIC)
+            .long(ATOMIC)
+            .required(false)
+            .action(ArgAction::SetTrue)
+            .help("Push branch and release tag atomically so that both or none of them land on remote"),
+        Arg::new(INCLUDE)
+            .long(INCLUDE)
+            .value_name(FILE)
+            .required(false)
+            .action(ArgAction::Append)
+            .help("Additional file (relative to root path) to include into version change commit (changelog for example). Can be set several times"),
+        Arg::new(ALLOW_DIRTY_TREE)
+            .long(ALLOW_DIRTY_TREE)
+            .required(false)
+            .action(ArgAction::SetTrue)
+            .help("Allow release from working tree that has uncommitted changes"),
+        vcs_arg(),
+    ]
+}
+
+fn remote_arg() -> Arg {
+    Arg::new(REMOTE)
+        .long(REMOTE)
+        .value_name(NAME)
+        .required(false)
+        .default_value("origin")
+}
+
+fn branch_arg() -> Arg {
+    Arg::new(BRANCH)
+        .long(BRANCH)
+        .value_name(NAME)
+        .required(false)
+        .help("Remote branch to push changes into. Current branch is pushed if not set")
+}
+
+fn vcs_arg() -> Arg {
+    Arg::new(VCS)
+        .long(VCS)
+        .value_name(BACKEND)
+        .value_parser(value_parser!(VcsBackend))
+        .required(false)
+        .default_value("git")
+        .help("Version control backend used to commit, tag and push changes")
+}
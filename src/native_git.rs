@@ -0,0 +1,455 @@
+use crate::{Error, Result};
+use git2::{
+    Commit, Cred, CredentialType, DescribeFormatOptions, DescribeOptions, ErrorCode, Index,
+    ObjectType, Oid, Repository, StatusOptions,
+};
+use std::path::{Path, PathBuf};
+
+use crate::{CommitOptions, PushOptions, TagOptions, Vcs, VcsFeature};
+
+const MAX_CREDENTIALS_ATTEMPTS: usize = 3;
+
+/// Version control backend built on top of libgit2.
+/// Doesn't require git executable so it can be used in minimal environments
+#[derive(Default)]
+pub struct NativeGit;
+
+impl Vcs for NativeGit {
+    fn commit<'a>(&'a self, path: &'a str, options: CommitOptions<'a>) -> Result<()> {
+        if options.sign {
+            return Err(Error::vcs(
+                "Commit signing isn't supported by native git backend. Use git backend instead",
+            ));
+        }
+        let repo = Repository::discover(path)?;
+        let prefix = workdir_prefix(&repo, path)?;
+
+        // Stage files like git add does. Ignored and not tracked files
+        // (Cargo.lock in libraries for example) are skipped
+        let mut repo_index = repo.index()?;
+        let mut files = vec![];
+        for file in options.files {
+            let relative = prefix.join(file);
+            let tracked = repo_index.get_path(&relative, 0).is_some();
+            if !tracked && repo.status_should_ignore(&relative)? {
+                continue;
+            }
+            repo_index.add_path(&relative)?;
+            files.push(relative);
+        }
+        if files.is_empty() {
+            return Err(Error::vcs("None of the files to commit is tracked by git"));
+        }
+        repo_index.write()?;
+
+        // Commit tree built from HEAD tree and the files specified only
+        // so that changes staged before release aren't committed
+        let head = repo.head()?.peel_to_commit()?;
+        let mut commit_index = Index::new()?;
+        commit_index.read_tree(&head.tree()?)?;
+        for file in &files {
+            if let Some(entry) = repo_index.get_path(file, 0) {
+                commit_index.add(&entry)?;
+            }
+        }
+        let tree = repo.find_tree(commit_index.write_tree_to(&repo)?)?;
+        let signature = repo.signature()?;
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            options.message,
+            &tree,
+            &[&head],
+        )?;
+        Ok(())
+    }
+
+    fn create_tag<'a>(
+        &'a self,
+        path: &'a str,
+        tag: &'a str,
+        options: TagOptions<'a>,
+    ) -> Result<()> {
+        if options.sign {
+            return Err(Error::vcs(
+                "Tag signing isn't supported by native git backend. Use git backend instead",
+            ));
+        }
+        let repo = Repository::discover(path)?;
+        let head = repo.head()?.peel(ObjectType::Commit)?;
+        if let Some(message) = options.message {
+            let signature = repo.signature()?;
+            repo.tag(tag, &head, &signature, message, false)?;
+        } else {
+            repo.tag_lightweight(tag, &head, false)?;
+        }
+        Ok(())
+    }
+
+    fn push_tag<'a>(&'a self, path: &'a str, tag: &'a str, options: PushOptions<'a>) -> Result<()> {
+        let repo = Repository::discover(path)?;
+        let tag = format!("refs/tags/{tag}");
+        push_refspecs(&repo, options.remote, &[format!("{tag}:{tag}")])
+    }
+
+    fn push<'a>(&'a self, path: &'a str, options: PushOptions<'a>) -> Result<()> {
+        let repo = Repository::discover(path)?;
+        let branch = current_branch(&repo)?;
+        let target = options
+            .branch
+            .map_or_else(|| branch.clone(), |b| format!("refs/heads/{b}"));
+        push_refspecs(&repo, options.remote, &[format!("{branch}:{target}")])
+    }
+
+    fn push_atomic<'a>(
+        &'a self,
+        _path: &'a str,
+        _tag: &'a str,
+        _options: PushOptions<'a>,
+    ) -> Result<()> {
+        Err(Error::vcs(
+            "Atomic push isn't supported by native git backend. Use git backend instead",
+        ))
+    }
+
+    fn is_clean(&self, path: &str) -> Result<bool> {
+        let repo = Repository::discover(path)?;
+        let mut options = StatusOptions::new();
+        options.include_untracked(false).include_ignored(false);
+        Ok(repo.statuses(Some(&mut options))?.is_empty())
+    }
+
+    fn last_tag(&self, path: &str) -> Result<Option<String>> {
+        let repo = Repository::discover(path)?;
+        if repo.tag_names(None)?.is_empty() {
+            return Ok(None);
+        }
+        let mut options = DescribeOptions::new();
+        options.describe_tags();
+        let mut format = DescribeFormatOptions::new();
+        format.abbreviated_size(0);
+        // none of tags is reachable from HEAD (release tagged on other branch for example)
+        let describe = match repo.describe(&options) {
+            Ok(describe) => describe,
+            Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(describe.format(Some(&format))?))
+    }
+
+    fn commits_since<'a>(&'a self, path: &'a str, tag: Option<&'a str>) -> Result<u32> {
+        let repo = Repository::discover(path)?;
+        let prefix = workdir_prefix(&repo, path)?;
+        let mut walk = repo.revwalk()?;
+        walk.push_head()?;
+        if let Some(tag) = tag {
+            walk.hide(repo.revparse_single(tag)?.peel_to_commit()?.id())?;
+        }
+        let mut count = 0;
+        for oid in walk {
+            let commit = repo.find_commit(oid?)?;
+            let before = if commit.parent_count() == 0 {
+                None
+            } else {
+                subtree_id(&commit.parent(0)?, &prefix)?
+            };
+            if subtree_id(&commit, &prefix)? != before {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    fn supports(&self, feature: VcsFeature) -> bool {
+        match feature {
+            VcsFeature::SignCommit | VcsFeature::SignTag | VcsFeature::AtomicPush => false,
+        }
+    }
+}
+
+/// Id of the commit's tree object at the path specified (relative to working directory)
+/// i.e. id that changes only if something under the path changed
+fn subtree_id(commit: &Commit, prefix: &Path) -> Result<Option<Oid>> {
+    let tree = commit.tree()?;
+    if prefix.as_os_str().is_empty() {
+        return Ok(Some(tree.id()));
+    }
+    Ok(tree.get_path(prefix).ok().map(|e| e.id()))
+}
+
+/// Path of the directory specified relative to repository working directory
+fn workdir_prefix(repo: &Repository, path: &str) -> Result<PathBuf> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| Error::vcs("Bare repositories aren't supported"))?
+        .canonicalize()?;
+    let path = Path::new(path).canonicalize()?;
+    path.strip_prefix(&workdir)
+        .map(Path::to_path_buf)
+        .map_err(|e| Error::Vcs {
+            message: format!("{} is outside of repository", path.display()),
+            source: Some(Box::new(e)),
+        })
+}
+
+/// Full reference name of the current branch
+fn current_branch(repo: &Repository) -> Result<String> {
+    let head = repo.head()?;
+    if !head.is_branch() {
+        return Err(Error::vcs("HEAD is detached so there is no branch to push"));
+    }
+    head.name()
+        .map(ToString::to_string)
+        .ok_or_else(|| Error::vcs("Current branch name isn't valid UTF-8"))
+}
+
+fn push_refspecs(repo: &Repository, remote: &str, refspecs: &[String]) -> Result<()> {
+    let mut remote = repo.find_remote(remote)?;
+    let config = repo.config()?;
+
+    let mut attempts = 0;
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(|url, username, allowed| {
+        // libgit2 asks credentials again and again while they're rejected
+        attempts += 1;
+        if attempts > MAX_CREDENTIALS_ATTEMPTS {
+            return Err(git2::Error::from_str("Authentication failed"));
+        }
+        if allowed.contains(CredentialType::SSH_KEY) {
+            Cred::ssh_key_from_agent(username.unwrap_or("git"))
+        } else if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            Cred::credential_helper(&config, url, username)
+        } else {
+            Cred::default()
+        }
+    });
+    callbacks.push_update_reference(|reference, status| match status {
+        Some(status) => Err(git2::Error::from_str(&format!(
+            "Push of {reference} rejected: {status}"
+        ))),
+        None => Ok(()),
+    });
+
+    let mut options = git2::PushOptions::new();
+    options.remote_callbacks(callbacks);
+    remote.push(refspecs, Some(&mut options))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_in_result)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn commit_only_files_specified() {
+        // Arrange
+        let (dir, repo) = new_repo();
+        write(&dir, "Cargo.toml", "version = \"0.2.0\"");
+        write(&dir, "README.md", "changed");
+        write(&dir, "CHANGELOG.md", "notes");
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("README.md")).unwrap();
+        index.write().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let files = ["Cargo.toml".to_string(), "CHANGELOG.md".to_string()];
+        let options = CommitOptions {
+            message: "changelog: v0.2.0",
+            files: &files,
+            sign: false,
+        };
+
+        // Act
+        let r = NativeGit.commit(path, options);
+
+        // Assert
+        assert!(r.is_ok());
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(Some("changelog: v0.2.0"), head.message());
+        let tree = head.tree().unwrap();
+        assert!(tree.get_name("CHANGELOG.md").is_some());
+        let readme = tree
+            .get_name("README.md")
+            .unwrap()
+            .to_object(&repo)
+            .unwrap();
+        assert_eq!(b"initial", readme.as_blob().unwrap().content());
+        assert!(!NativeGit.is_clean(path).unwrap());
+    }
+
+    #[test]
+    fn commit_signed_not_supported() {
+        // Arrange
+        let (dir, _repo) = new_repo();
+        let files = ["Cargo.toml".to_string()];
+        let options = CommitOptions {
+            message: "changelog: v0.2.0",
+            files: &files,
+            sign: true,
+        };
+
+        // Act
+        let r = NativeGit.commit(dir.path().to_str().unwrap(), options);
+
+        // Assert
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn create_annotated_tag() {
+        // Arrange
+        let (dir, repo) = new_repo();
+        let options = TagOptions {
+            message: Some("release notes"),
+            sign: false,
+        };
+
+        // Act
+        let r = NativeGit.create_tag(dir.path().to_str().unwrap(), "v0.1.0", options);
+
+        // Assert
+        assert!(r.is_ok());
+        let tag = repo.revparse_single("v0.1.0").unwrap().into_tag().unwrap();
+        assert_eq!(Some("release notes"), tag.message());
+    }
+
+    #[test]
+    fn create_lightweight_tag() {
+        // Arrange
+        let (dir, repo) = new_repo();
+
+        // Act
+        let r = NativeGit.create_tag(
+            dir.path().to_str().unwrap(),
+            "v0.1.0",
+            TagOptions::default(),
+        );
+
+        // Assert
+        assert!(r.is_ok());
+        let tag = repo.revparse_single("v0.1.0").unwrap();
+        assert_eq!(Some(ObjectType::Commit), tag.kind());
+    }
+
+    #[test]
+    fn push_branch_and_tag_into_bare_repo() {
+        // Arrange
+        let (dir, repo) = new_repo();
+        let remote_dir = TempDir::new().unwrap();
+        let remote = Repository::init_bare(remote_dir.path()).unwrap();
+        repo.remote("upstream", remote_dir.path().to_str().unwrap())
+            .unwrap();
+        let path = dir.path().to_str().unwrap();
+        NativeGit
+            .create_tag(path, "v0.1.0", TagOptions::default())
+            .unwrap();
+        let options = PushOptions {
+            remote: "upstream",
+            branch: Some("release"),
+        };
+
+        // Act
+        let branch = NativeGit.push(path, options);
+        let tag = NativeGit.push_tag(
+            path,
+            "v0.1.0",
+            PushOptions {
+                remote: "upstream",
+                branch: None,
+            },
+        );
+
+        // Assert
+        assert!(branch.is_ok());
+        assert!(tag.is_ok());
+        let head = repo.head().unwrap().target().unwrap();
+        let pushed = remote.refname_to_id("refs/heads/release").unwrap();
+        assert_eq!(head, pushed);
+        assert!(remote.refname_to_id("refs/tags/v0.1.0").is_ok());
+    }
+
+    #[test]
+    fn last_tag_not_reachable_from_head() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init_bare(dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let signature = git2::Signature::now("releaser", "releaser@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])
+            .unwrap();
+        let other = repo
+            .commit(None, &signature, &signature, "other", &tree, &[])
+            .unwrap();
+        repo.tag_lightweight("v0.1.0", &repo.find_object(other, None).unwrap(), false)
+            .unwrap();
+
+        // Act
+        let tag = NativeGit.last_tag(dir.path().to_str().unwrap());
+
+        // Assert
+        assert_eq!(None, tag.unwrap());
+    }
+
+    #[test]
+    fn last_tag_reachable_from_head() {
+        // Arrange
+        let (dir, _repo) = new_repo();
+        let path = dir.path().to_str().unwrap();
+        NativeGit
+            .create_tag(path, "v0.1.0", TagOptions::default())
+            .unwrap();
+
+        // Act
+        let tag = NativeGit.last_tag(path);
+
+        // Assert
+        assert_eq!(Some("v0.1.0".to_string()), tag.unwrap());
+    }
+
+    #[test]
+    fn is_clean_untracked_files_ignored() {
+        // Arrange
+        let (dir, _repo) = new_repo();
+        write(&dir, "new.txt", "new");
+
+        // Act
+        let clean = NativeGit.is_clean(dir.path().to_str().unwrap());
+
+        // Assert
+        assert!(clean.unwrap());
+    }
+
+    fn new_repo() -> (TempDir, Repository) {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("user.name", "releaser").unwrap();
+            config
+                .set_str("user.email", "releaser@example.com")
+                .unwrap();
+        }
+        write(&dir, "Cargo.toml", "version = \"0.1.0\"");
+        write(&dir, "README.md", "initial");
+        {
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("Cargo.toml")).unwrap();
+            index.add_path(Path::new("README.md")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let signature = repo.signature().unwrap();
+            repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])
+                .unwrap();
+        }
+        (dir, repo)
+    }
+
+    fn write(dir: &TempDir, file: &str, content: &str) {
+        std::fs::write(dir.path().join(file), content).unwrap();
+    }
+}
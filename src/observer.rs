@@ -0,0 +1,198 @@
+use console::style;
+use semver::Version;
+
+/// Why crate isn't published
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SkipReason {
+    /// Crate's publish setting doesn't allow the registry
+    NotAllowed,
+    /// Crate's new version is already in registry index
+    AlreadyPublished,
+}
+
+/// Release progress event
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ReleaseEvent<'a> {
+    /// New version of crate or workspace computed
+    VersionComputed { version: &'a Version },
+    /// Manifest (path relative to root) rewritten with new version
+    FileRewritten { path: &'a str },
+    /// Crates (crate at root if empty) are being packaged and verified
+    Packaging { crates: &'a [String] },
+    /// Hook command is being run
+    HookRunning { command: &'a str },
+    /// Version change reverted because release failed before it was committed
+    Reverted,
+    /// Version change committed
+    Committed {
+        message: &'a str,
+        files: &'a [String],
+    },
+    /// Crate is being published
+    Publishing {
+        name: &'a str,
+        registry: Option<&'a str>,
+    },
+    /// Crate published
+    Published {
+        name: &'a str,
+        registry: Option<&'a str>,
+    },
+    /// Crate isn't published
+    Skipped {
+        name: &'a str,
+        version: &'a str,
+        registry: Option<&'a str>,
+        reason: SkipReason,
+    },
+    /// Publish failed with transient error and will be retried after delay
+    Retrying {
+        error: &'a str,
+        delay_seconds: u64,
+        attempt: u32,
+        retries: u32,
+    },
+    /// Waiting for published crate to appear in registry index
+    Waiting {
+        name: &'a str,
+        version: &'a str,
+        delay_seconds: u64,
+    },
+    /// Release tag created
+    Tagged { tag: &'a str },
+    /// Branch (current if not set) pushed to remote
+    Pushed {
+        remote: &'a str,
+        branch: Option<&'a str>,
+    },
+    /// Release tag pushed to remote
+    TagPushed { remote: &'a str, tag: &'a str },
+    /// Branch (current if not set) and release tag pushed to remote atomically
+    PushedAtomic {
+        remote: &'a str,
+        branch: Option<&'a str>,
+        tag: &'a str,
+    },
+    /// Release failed
+    Failed { error: &'a str },
+}
+
+/// Receives release progress events so that tools embedding releaser
+/// can show their own UI or collect logs
+pub trait ReleaseObserver {
+    /// Called on each release step. `dry_run` is set if step isn't actually made
+    fn on_event(&self, event: &ReleaseEvent<'_>, dry_run: bool);
+}
+
+/// Observer that prints progress into console
+#[derive(Default, Debug)]
+pub struct ConsoleObserver;
+
+impl ReleaseObserver for ConsoleObserver {
+    fn on_event(&self, event: &ReleaseEvent<'_>, dry_run: bool) {
+        match *event {
+            ReleaseEvent::Skipped {
+                name,
+                version,
+                registry,
+                reason,
+            } => {
+                let registry = registry.unwrap_or("default");
+                match reason {
+                    SkipReason::NotAllowed => println!(
+                        " Skipping {} because its publish setting doesn't allow publishing to {} registry",
+                        style(name).yellow().bold(),
+                        style(registry).yellow().bold()
+                    ),
+                    SkipReason::AlreadyPublished => println!(
+                        " Skipping {} {} because it's already published to {} registry",
+                        style(name).yellow().bold(),
+                        style(version).yellow().bold(),
+                        style(registry).yellow().bold()
+                    ),
+                }
+            }
+            ReleaseEvent::Retrying {
+                error,
+                delay_seconds,
+                attempt,
+                retries,
+            } => println!(
+                " {} Retrying in {} seconds ({attempt}/{retries}) ...",
+                style(error).yellow(),
+                style(delay_seconds).green().bold(),
+            ),
+            ReleaseEvent::Waiting {
+                name,
+                version,
+                delay_seconds,
+            } => println!(
+                " Waiting up to {} seconds for {} {} to appear in registry index ...",
+                style(delay_seconds).green().bold(),
+                style(name).green().bold(),
+                style(version).green().bold()
+            ),
+            ReleaseEvent::Reverted => {
+                println!(" {}", style("Version change reverted").yellow());
+            }
+            _ if dry_run => print_dry_run(event),
+            _ => {}
+        }
+    }
+}
+
+/// Prints steps that would be made. Real steps aren't printed
+/// because git and cargo output is shown instead
+fn print_dry_run(event: &ReleaseEvent<'_>) {
+    match *event {
+        ReleaseEvent::Packaging { crates } => {
+            let crates = if crates.is_empty() {
+                "crate".to_string()
+            } else {
+                crates.join(", ")
+            };
+            println!(
+                " Dry run: would verify packages {}",
+                style(crates).green().bold()
+            );
+        }
+        ReleaseEvent::HookRunning { command } => {
+            println!(" Dry run: would run {}", style(command).green().bold());
+        }
+        ReleaseEvent::Committed { message, files } => println!(
+            " Dry run: would commit {} with message {}",
+            style(files.join(", ")).green().bold(),
+            style(message).green().bold()
+        ),
+        ReleaseEvent::Publishing { name, registry } => println!(
+            " Dry run: would publish {} to {} registry",
+            style(name).green().bold(),
+            style(registry.unwrap_or("default")).green().bold()
+        ),
+        ReleaseEvent::Tagged { tag } => {
+            println!(" Dry run: would create tag {}", style(tag).green().bold());
+        }
+        ReleaseEvent::Pushed { remote, branch } => println!(
+            " Dry run: would push {} to {}",
+            style(branch.unwrap_or("current branch")).green().bold(),
+            style(remote).green().bold()
+        ),
+        ReleaseEvent::TagPushed { remote, tag } => println!(
+            " Dry run: would push tag {} to {}",
+            style(tag).green().bold(),
+            style(remote).green().bold()
+        ),
+        ReleaseEvent::PushedAtomic {
+            remote,
+            branch,
+            tag,
+        } => println!(
+            " Dry run: would push {} and tag {} to {} atomically",
+            style(branch.unwrap_or("current branch")).green().bold(),
+            style(tag).green().bold(),
+            style(remote).green().bold()
+        ),
+        _ => {}
+    }
+}
@@ -1,7 +1,9 @@
 use crate::hash;
 use crate::resource::Resource;
-use color_eyre::eyre::{OptionExt, Result};
+use crate::{Error, Result};
+use flate2::read::GzDecoder;
 use serde::Serialize;
+use tar::Archive;
 use vfs::VfsPath;
 
 const PKG_EXTENSION: &str = "gz";
@@ -13,7 +15,13 @@ pub struct Package {
 }
 
 pub fn new_binary_pkg(path: &VfsPath, base_uri: &str) -> Result<Package> {
-    let (hash, file) = calculate_sha256(path)?;
+    new_pkg(path, base_uri, &[PKG_EXTENSION])
+}
+
+/// Creates package from the first file with one of the extensions specified
+/// found in the directory (dmg or pkg installer for example)
+pub fn new_pkg(path: &VfsPath, base_uri: &str, extensions: &[&str]) -> Result<Package> {
+    let (hash, file) = calculate_sha256(path, extensions)?;
     let mut resource = Resource::new(base_uri)?;
     resource.append_path(&file);
     Ok(Package {
@@ -22,21 +30,73 @@ pub fn new_binary_pkg(path: &VfsPath, base_uri: &str) -> Result<Package> {
     })
 }
 
-fn calculate_sha256(path: &VfsPath) -> Result<(String, String)> {
-    let mut it = path.read_dir()?;
-    let file_name = it.find(|x| x.extension().is_some_and(|ext| ext.eq(PKG_EXTENSION)));
-    let file_name =
-        file_name.ok_or_eyre(format!("No file with extension {PKG_EXTENSION} found"))?;
+/// Lists files in package archive (tar.gz) found in the directory specified.
+/// If all files are under single top level directory (it's removed on unpack
+/// by package managers) paths are relative to it
+pub fn archive_files(path: &VfsPath) -> Result<Vec<String>> {
+    let files = archive_entries(path)?;
+    if let Some(top) = top_dir(&files) {
+        let prefix = format!("{top}/");
+        return Ok(files
+            .iter()
+            .map(|f| f[prefix.len()..].to_string())
+            .collect());
+    }
+    Ok(files)
+}
+
+/// Single top level directory all files of package archive (tar.gz) found in the
+/// directory specified are under. None if there are files outside of it
+pub fn archive_top_dir(path: &VfsPath) -> Result<Option<String>> {
+    let files = archive_entries(path)?;
+    Ok(top_dir(&files).map(ToString::to_string))
+}
 
+fn archive_entries(path: &VfsPath) -> Result<Vec<String>> {
+    let file = find_package(path, &[PKG_EXTENSION])?;
+    let mut archive = Archive::new(GzDecoder::new(file.open_file()?));
+    let mut files = vec![];
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_file() {
+            let path = entry.path()?.to_string_lossy().replace('\\', "/");
+            files.push(path.trim_start_matches("./").to_string());
+        }
+    }
+    Ok(files)
+}
+
+fn top_dir(files: &[String]) -> Option<&str> {
+    let (top, _) = files.first()?.split_once('/')?;
+    files
+        .iter()
+        .all(|f| f.strip_prefix(top).is_some_and(|r| r.starts_with('/')))
+        .then_some(top)
+}
+
+fn calculate_sha256(path: &VfsPath, extensions: &[&str]) -> Result<(String, String)> {
+    let file_name = find_package(path, extensions)?;
     let hash = hash::calculate_sha256(&file_name)?;
     Ok((hash, file_name.filename()))
 }
 
+fn find_package(path: &VfsPath, extensions: &[&str]) -> Result<VfsPath> {
+    let mut it = path.read_dir()?;
+    let file_name = it.find(|x| x.extension().is_some_and(|ext| extensions.contains(&&*ext)));
+    file_name.ok_or_else(|| {
+        Error::packaging(format!(
+            "No file with extension {} found",
+            extensions.join(" or ")
+        ))
+    })
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     #![allow(clippy::unwrap_in_result)]
     #![allow(clippy::unwrap_used)]
     use super::*;
+    use rstest::rstest;
     use vfs::MemoryFS;
 
     #[test]
@@ -61,6 +121,89 @@ mod tests {
         assert_eq!(p.url.as_str(), "http://x/f.tar.gz");
     }
 
+    #[rstest]
+    #[case::dmg("f.dmg", Some("http://x/f.dmg"))]
+    #[case::pkg("f.pkg", Some("http://x/f.pkg"))]
+    #[case::other("f.tar.gz", None)]
+    #[trace]
+    fn new_pkg_tests(#[case] file: &str, #[case] expected: Option<&str>) {
+        // Arrange
+        let root: VfsPath = MemoryFS::new().into();
+        root.join(file)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all("123".as_bytes())
+            .unwrap();
+
+        // Act
+        let p = new_pkg(&root, "http://x", &["dmg", "pkg"]);
+
+        // Assert
+        assert_eq!(expected, p.ok().map(|p| p.url).as_deref());
+    }
+
+    #[rstest]
+    #[case::flat(&["solv", "solv.1"], vec!["solv", "solv.1"])]
+    #[case::top_dir(&["solv-1.0/solv", "solv-1.0/doc/solv.1"], vec!["solv", "doc/solv.1"])]
+    #[case::different_dirs(&["a/solv", "b/solv.1"], vec!["a/solv", "b/solv.1"])]
+    #[trace]
+    fn archive_files_tests(#[case] files: &[&str], #[case] expected: Vec<&str>) {
+        // Arrange
+        let root: VfsPath = MemoryFS::new().into();
+        root.join("solv.tar.gz")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(&tar_gz(files))
+            .unwrap();
+
+        // Act
+        let actual = archive_files(&root).unwrap();
+
+        // Assert
+        assert_eq!(expected, actual);
+    }
+
+    #[rstest]
+    #[case::flat(&["solv", "solv.1"], None)]
+    #[case::top_dir(&["solv-1.0/solv", "solv-1.0/doc/solv.1"], Some("solv-1.0"))]
+    #[case::different_dirs(&["a/solv", "b/solv.1"], None)]
+    #[case::common_prefix(&["solv/solv", "solv-1.0/solv.1"], None)]
+    #[trace]
+    fn archive_top_dir_tests(#[case] files: &[&str], #[case] expected: Option<&str>) {
+        // Arrange
+        let root: VfsPath = MemoryFS::new().into();
+        root.join("solv.tar.gz")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(&tar_gz(files))
+            .unwrap();
+
+        // Act
+        let actual = archive_top_dir(&root).unwrap();
+
+        // Assert
+        assert_eq!(expected, actual.as_deref());
+    }
+
+    /// Creates tar.gz archive content with files specified
+    pub fn tar_gz(files: &[&str]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for file in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(3);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, file, "123".as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
     #[test]
     fn new_binary_pkg_gz_file_not_exists_test() {
         // Arrange
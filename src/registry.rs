@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+use serde::Deserialize;
+use ureq::tls::{RootCerts, TlsConfig};
+use url::Url;
+
+use crate::Index;
+use crate::resource::Resource;
+
+const CRATES_IO: &str = "crates-io";
+const CRATES_IO_INDEX: &str = "sparse+https://index.crates.io/";
+const SPARSE_PREFIX: &str = "sparse+";
+const CARGO_DIR: &str = ".cargo";
+const CONFIG_FILES: [&str; 2] = ["config.toml", "config"];
+
+/// Registry index located using cargo configuration i.e. `registries.<name>.index`
+/// setting from `.cargo/config.toml` files or `CARGO_REGISTRIES_<NAME>_INDEX` environment variable.
+/// Sparse (HTTP) and local (`file://`) indexes supported
+pub struct CargoIndex {
+    root: PathBuf,
+}
+
+impl CargoIndex {
+    /// Creates index which configuration is searched starting from root path specified
+    #[must_use]
+    pub fn new(root: &str) -> Self {
+        let root = std::path::absolute(root).unwrap_or_else(|_| PathBuf::from(root));
+        Self { root }
+    }
+
+    /// Finds index URL of the registry specified. crates.io index used if registry isn't set
+    fn index_url(&self, registry: Option<&str>) -> Result<String> {
+        let Some(registry) = registry.filter(|r| *r != CRATES_IO) else {
+            return Ok(CRATES_IO_INDEX.to_string());
+        };
+
+        if let Some(url) = index_url_from_env(registry) {
+            return Ok(url);
+        }
+
+        let home = cargo_home();
+        let config_dirs = self.root.ancestors().map(|d| d.join(CARGO_DIR)).chain(home);
+        for dir in config_dirs {
+            for file in CONFIG_FILES {
+                let path = dir.join(file);
+                if let Some(url) = index_url_from_config(&path, registry)? {
+                    return Ok(url);
+                }
+            }
+        }
+        Err(Error::config(format!(
+            "Index of registry {registry} not found in cargo configuration"
+        )))
+    }
+}
+
+impl Index for CargoIndex {
+    fn contains<'a>(
+        &'a self,
+        registry: Option<&'a str>,
+        name: &'a str,
+        version: &'a str,
+    ) -> Result<bool> {
+        let url = self.index_url(registry)?;
+        let entries = read_entries(&url, name)?;
+        Ok(entries.is_some_and(|e| contains_version(&e, version)))
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct CargoConfig {
+    registries: Option<HashMap<String, RegistryConfig>>,
+}
+
+#[derive(Deserialize)]
+struct RegistryConfig {
+    index: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IndexEntry {
+    vers: String,
+}
+
+fn index_url_from_env(registry: &str) -> Option<String> {
+    let name = registry.to_uppercase().replace('-', "_");
+    env::var(format!("CARGO_REGISTRIES_{name}_INDEX")).ok()
+}
+
+fn index_url_from_config(path: &Path, registry: &str) -> Result<Option<String>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)?;
+    let config: CargoConfig =
+        toml::from_str(&content).map_err(|e| Error::toml(path.display(), e))?;
+    Ok(config
+        .registries
+        .and_then(|mut r| r.remove(registry))
+        .and_then(|r| r.index))
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    env::var_os("CARGO_HOME").map(PathBuf::from).or_else(|| {
+        env::var_os("HOME")
+            .or_else(|| env::var_os("USERPROFILE"))
+            .map(|h| PathBuf::from(h).join(CARGO_DIR))
+    })
+}
+
+/// Reads crate's index file content. None returned if crate isn't in the index
+fn read_entries(index_url: &str, name: &str) -> Result<Option<String>> {
+    let file = index_file_path(name);
+    if let Some(sparse) = index_url.strip_prefix(SPARSE_PREFIX) {
+        let mut resource = Resource::new(sparse)?;
+        resource.append_path(&file);
+        match http_agent().get(resource.to_string()).call() {
+            Ok(mut response) => Ok(Some(response.body_mut().read_to_string()?)),
+            Err(ureq::Error::StatusCode(404 | 410 | 451)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    } else {
+        let url = Url::parse(index_url)?;
+        if url.scheme() != "file" {
+            return Err(Error::registry(format!(
+                "Only sparse and local (file://) registry indexes supported but {index_url} set"
+            )));
+        }
+        let dir = url
+            .to_file_path()
+            .map_err(|()| Error::config(format!("Invalid local index path {index_url}")))?;
+        let path = dir.join(file);
+        if path.is_file() {
+            Ok(Some(std::fs::read_to_string(path)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// HTTP client that trusts platform certificates so that it works behind corporate proxies too
+fn http_agent() -> ureq::Agent {
+    let tls = TlsConfig::builder()
+        .root_certs(RootCerts::PlatformVerifier)
+        .build();
+    ureq::Agent::config_builder()
+        .tls_config(tls)
+        .build()
+        .new_agent()
+}
+
+/// Crate's file path relative to index root according to cargo index layout
+fn index_file_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
+
+/// Whether index file content (one JSON document per line) has version specified
+fn contains_version(entries: &str, version: &str) -> bool {
+    entries
+        .lines()
+        .filter_map(|line| serde_json::from_str::<IndexEntry>(line).ok())
+        .any(|e| e.vers == version)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_in_result)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    #[rstest]
+    #[case::one_letter("a", "1/a")]
+    #[case::two_letters("ab", "2/ab")]
+    #[case::three_letters("abc", "3/a/abc")]
+    #[case::four_letters("abcd", "ab/cd/abcd")]
+    #[case::long("releaser", "re/le/releaser")]
+    #[case::uppercase("Inflector", "in/fl/inflector")]
+    #[trace]
+    fn index_file_path_tests(#[case] name: &str, #[case] expected: &str) {
+        // Arrange
+
+        // Act
+        let actual = index_file_path(name);
+
+        // Assert
+        assert_eq!(expected, actual);
+    }
+
+    #[rstest]
+    #[case::exist("0.16.1", true)]
+    #[case::first("0.16.0", true)]
+    #[case::not_exist("0.17.0", false)]
+    #[trace]
+    fn contains_version_tests(#[case] version: &str, #[case] expected: bool) {
+        // Arrange
+
+        // Act
+        let actual = contains_version(ENTRIES, version);
+
+        // Assert
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn index_url_crates_io() {
+        // Arrange
+        let index = CargoIndex::new("/");
+
+        // Act
+        let default = index.index_url(None).unwrap();
+        let named = index.index_url(Some(CRATES_IO)).unwrap();
+
+        // Assert
+        assert_eq!(CRATES_IO_INDEX, default);
+        assert_eq!(CRATES_IO_INDEX, named);
+    }
+
+    #[test]
+    fn index_url_from_parent_config() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let config_dir = dir.path().join(CARGO_DIR);
+        std::fs::create_dir(&config_dir).unwrap();
+        std::fs::write(config_dir.join("config.toml"), CONFIG).unwrap();
+        let member = dir.path().join("member");
+        std::fs::create_dir(&member).unwrap();
+        let index = CargoIndex::new(member.to_str().unwrap());
+
+        // Act
+        let url = index.index_url(Some("my-registry"));
+
+        // Assert
+        assert_eq!("sparse+https://registry.example.com/index/", url.unwrap());
+    }
+
+    #[test]
+    fn contains_local_index() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let crate_dir = dir.path().join("so").join("lv");
+        std::fs::create_dir_all(&crate_dir).unwrap();
+        std::fs::write(crate_dir.join("solv"), ENTRIES).unwrap();
+        let config_dir = dir.path().join(CARGO_DIR);
+        std::fs::create_dir(&config_dir).unwrap();
+        let url = Url::from_directory_path(dir.path()).unwrap();
+        let config = format!("[registries.local]\nindex = \"{url}\"\n");
+        std::fs::write(config_dir.join("config.toml"), config).unwrap();
+        let index = CargoIndex::new(dir.path().to_str().unwrap());
+
+        // Act
+        let published = index.contains(Some("local"), "solv", "0.16.1").unwrap();
+        let not_published = index.contains(Some("local"), "solv", "1.0.0").unwrap();
+        let unknown = index.contains(Some("local"), "solp", "0.16.1").unwrap();
+
+        // Assert
+        assert!(published);
+        assert!(!not_published);
+        assert!(!unknown);
+    }
+
+    #[test]
+    fn contains_git_index_not_supported() {
+        // Arrange
+
+        // Act
+        let result = read_entries("https://github.com/rust-lang/crates.io-index", "solv");
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    const ENTRIES: &str = r#"{"name":"solv","vers":"0.16.0","deps":[],"cksum":"00","features":{},"yanked":false}
+{"name":"solv","vers":"0.16.1","deps":[],"cksum":"01","features":{},"yanked":false}
+"#;
+
+    const CONFIG: &str = r#"
+[registries.my-registry]
+index = "sparse+https://registry.example.com/index/"
+"#;
+}
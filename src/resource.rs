@@ -1,4 +1,4 @@
-use color_eyre::eyre::Result;
+use crate::Result;
 use core::fmt;
 use url::Url;
 
@@ -1,65 +1,274 @@
-use crate::{CrateConfig, new_cargo_config_path, packaging};
-use color_eyre::eyre::Result;
-use serde::Serialize;
+use crate::{CrateConfig, GITHUB, new_cargo_config_path, packaging};
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
 use vfs::VfsPath;
 
+/// Manifest fields are in the order Scoop buckets use
 #[derive(Serialize, Default)]
 pub struct Scoop {
+    pub version: String,
     pub description: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub homepage: Option<String>,
-    pub version: String,
     pub license: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depends: Option<Vec<String>>,
     pub architecture: Architecture,
+    /// Start menu shortcuts as (executable, shortcut name) pairs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shortcuts: Option<Vec<[String; 2]>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persist: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_add_path: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkver: Option<CheckVer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autoupdate: Option<AutoUpdate>,
 }
 
 #[derive(Serialize, Default)]
 pub struct Architecture {
     #[serde(rename(serialize = "64bit"))]
     pub x64: Binary,
+    #[serde(rename(serialize = "32bit"), skip_serializing_if = "Option::is_none")]
+    pub x86: Option<Binary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arm64: Option<Binary>,
+}
+
+/// How Scoop's Excavator finds out the latest version
+#[derive(Serialize, Default)]
+pub struct CheckVer {
+    /// GitHub repository which latest release is the latest version
+    pub github: String,
+}
+
+/// How Scoop's Excavator updates manifest to the latest version.
+/// `$version` in URLs replaced by the version found by checkver
+#[derive(Serialize, Default)]
+pub struct AutoUpdate {
+    pub architecture: AutoUpdateArchitecture,
+}
+
+#[derive(Serialize, Default)]
+pub struct AutoUpdateArchitecture {
+    #[serde(rename(serialize = "64bit"))]
+    pub x64: AutoUpdateBinary,
+    #[serde(rename(serialize = "32bit"), skip_serializing_if = "Option::is_none")]
+    pub x86: Option<AutoUpdateBinary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arm64: Option<AutoUpdateBinary>,
+}
+
+#[derive(Serialize, Default)]
+pub struct AutoUpdateBinary {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extract_dir: Option<String>,
 }
 
 #[derive(Serialize, Default)]
 pub struct Binary {
     pub url: String,
     pub hash: Option<String>,
-    pub bin: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extract_dir: Option<String>,
+    pub bin: Vec<Bin>,
+}
+
+/// Executable added to PATH
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Bin {
+    /// Executable available by its file name
+    Path(String),
+    /// Executable and alias it's available by
+    Alias([String; 2]),
+}
+
+/// Manifest settings that cannot be derived from crate and packages. Can also be set
+/// in crate's manifest `[package.metadata.releaser.scoop]` section
+#[derive(Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ScoopConfig {
+    /// Package archive directory to extract. Archive's top level directory used if not set
+    pub extract_dir: Option<String>,
+    /// Name executable is available by in command line instead of its file name
+    pub alias: Option<String>,
+    /// Start menu shortcut name of executable
+    pub shortcut: Option<String>,
+    /// Files and directories kept between updates
+    pub persist: Option<Vec<String>>,
+    /// Directories (relative to app directory) added to PATH
+    pub env_add_path: Option<Vec<String>>,
+    /// Apps installed before this one
+    pub depends: Option<Vec<String>>,
+    /// Message lines shown after installation
+    pub notes: Option<Vec<String>>,
+}
+
+impl ScoopConfig {
+    /// Settings of this config complemented by ones set in the other config
+    #[must_use]
+    pub fn or(self, other: Option<&Self>) -> Self {
+        let Some(other) = other else {
+            return self;
+        };
+        let other = other.clone();
+        Self {
+            extract_dir: self.extract_dir.or(other.extract_dir),
+            alias: self.alias.or(other.alias),
+            shortcut: self.shortcut.or(other.shortcut),
+            persist: self.persist.or(other.persist),
+            env_add_path: self.env_add_path.or(other.env_add_path),
+            depends: self.depends.or(other.depends),
+            notes: self.notes.or(other.notes),
+        }
+    }
 }
 
 impl Scoop {
-    /// Creates Scoop struct instance and serializes it to String
+    /// Creates Scoop struct instance and serializes it to String.
+    /// 32-bit and ARM64 packages are optional. Settings in config specified
+    /// override ones set in crate's manifest
     pub fn serialize(
         crate_path: &VfsPath,
         binary_path: &VfsPath,
+        binary32_path: Option<&VfsPath>,
+        binary_arm_path: Option<&VfsPath>,
         executable_name: &str,
         base_uri: &str,
+        config: ScoopConfig,
     ) -> Result<String> {
         let crate_conf = new_cargo_config_path(crate_path)?;
-        let config = CrateConfig::open(&crate_conf)?;
-        let binary = packaging::new_binary_pkg(binary_path, base_uri)?;
-        let x64pkg = Binary {
-            url: binary.url,
-            hash: Some(binary.hash),
-            bin: vec![executable_name.to_string()],
+        let crate_config = CrateConfig::open(&crate_conf)?;
+        let config = config.or(crate_config.package.scoop_config());
+        let bin = match &config.alias {
+            Some(alias) => Bin::Alias([executable_name.to_string(), alias.clone()]),
+            None => Bin::Path(executable_name.to_string()),
+        };
+        let new_binary = |path: &VfsPath| -> Result<Binary> {
+            let binary = packaging::new_binary_pkg(path, base_uri)?;
+            let extract_dir = match &config.extract_dir {
+                Some(dir) => Some(dir.clone()),
+                None => packaging::archive_top_dir(path).ok().flatten(),
+            };
+            Ok(Binary {
+                url: binary.url,
+                hash: Some(binary.hash),
+                extract_dir,
+                bin: vec![bin.clone()],
+            })
         };
+        let architecture = Architecture {
+            x64: new_binary(binary_path)?,
+            x86: binary32_path.map(new_binary).transpose()?,
+            arm64: binary_arm_path.map(new_binary).transpose()?,
+        };
+
+        let checkver = crate_config
+            .package
+            .repository
+            .filter(|r| r.starts_with(GITHUB))
+            .map(|github| CheckVer { github });
+        let autoupdate = new_autoupdate(&architecture, &crate_config.package.version)?;
+        let shortcuts = config
+            .shortcut
+            .map(|name| vec![[executable_name.to_string(), name]]);
 
         let scoop = Scoop {
-            description: config.package.description.unwrap_or_default(),
-            homepage: config.package.homepage,
-            version: config.package.version,
-            license: config.package.license.unwrap_or_default(),
-            architecture: Architecture { x64: x64pkg },
+            version: crate_config.package.version,
+            description: crate_config.package.description.unwrap_or_default(),
+            homepage: crate_config.package.homepage,
+            license: crate_config.package.license.unwrap_or_default(),
+            notes: config.notes,
+            depends: config.depends,
+            architecture,
+            shortcuts,
+            persist: config.persist,
+            env_add_path: config.env_add_path,
+            checkver,
+            autoupdate,
         };
         Ok(serde_json::to_string_pretty(&scoop)?)
     }
 }
 
+/// Autoupdate of packages which URLs contain version. None if 64-bit package URL doesn't
+/// contain version because Excavator cannot figure out new version's URL then.
+/// Error if 64-bit package URL contains version but other architecture's one doesn't
+/// because autoupdate would leave that architecture stale
+fn new_autoupdate(architecture: &Architecture, version: &str) -> Result<Option<AutoUpdate>> {
+    let new_binary = |binary: &Binary| {
+        Some(AutoUpdateBinary {
+            url: templatize_version(&binary.url, version)?,
+            extract_dir: binary
+                .extract_dir
+                .as_ref()
+                .map(|d| templatize_version(d, version).unwrap_or_else(|| d.clone())),
+        })
+    };
+    let Some(x64) = new_binary(&architecture.x64) else {
+        return Ok(None);
+    };
+    let new_other = |binary: Option<&Binary>, name: &str| {
+        binary
+            .map(|b| {
+                new_binary(b).ok_or_else(|| {
+                    Error::packaging(format!(
+                        "{name} package URL {} doesn't contain version {version} so autoupdate cannot update it",
+                        b.url
+                    ))
+                })
+            })
+            .transpose()
+    };
+    Ok(Some(AutoUpdate {
+        architecture: AutoUpdateArchitecture {
+            x64,
+            x86: new_other(architecture.x86.as_ref(), "32bit")?,
+            arm64: new_other(architecture.arm64.as_ref(), "arm64")?,
+        },
+    }))
+}
+
+/// Replaces version in URL by `$version` variable. Version's occurrences that are part
+/// of a longer version (1.0.1 in 1.0.12 for example) aren't replaced. None if URL doesn't contain version
+fn templatize_version(url: &str, version: &str) -> Option<String> {
+    let mut result = String::with_capacity(url.len());
+    let mut found = false;
+    let mut pos = 0;
+    for (start, _) in url.match_indices(version) {
+        let end = start + version.len();
+        let before = url[..start].chars().next_back();
+        let mut after = url[end..].chars();
+        let separated = !before.is_some_and(|c| c.is_ascii_digit() || c == '.')
+            && match after.next() {
+                Some(c) if c.is_ascii_digit() => false,
+                Some('.') => !after.next().is_some_and(|c| c.is_ascii_digit()),
+                _ => true,
+            };
+        if separated {
+            result.push_str(&url[pos..start]);
+            result.push_str("$version");
+            pos = end;
+            found = true;
+        }
+    }
+    result.push_str(&url[pos..]);
+    found.then_some(result)
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_in_result)]
     #![allow(clippy::unwrap_used)]
     use super::*;
     use crate::CARGO_CONFIG;
+    use crate::packaging::tests::tar_gz;
     use rstest::{fixture, rstest};
     use vfs::MemoryFS;
 
@@ -69,16 +278,78 @@ mod tests {
         let binary_path = root.join("x64").unwrap();
 
         // Act
-        let result = Scoop::serialize(&root, &binary_path, "solv.exe", "http://localhost");
+        let result = Scoop::serialize(
+            &root,
+            &binary_path,
+            None,
+            None,
+            "solv.exe",
+            "http://localhost",
+            ScoopConfig::default(),
+        );
 
         // Assert
         assert!(result.is_ok());
         assert_eq!(
             result.unwrap().as_str(),
             r#"{
+  "version": "0.1.13",
   "description": "Microsoft Visual Studio solution parsing library",
   "homepage": "https://github.com/aegoroff/solv",
+  "license": "MIT",
+  "architecture": {
+    "64bit": {
+      "url": "http://localhost/solv.tar.gz",
+      "hash": "a665a45920422f9d417e4867efdc4fb8a04a1f3fff1fa07e998e86f7f7a27ae3",
+      "bin": [
+        "solv.exe"
+      ]
+    }
+  },
+  "checkver": {
+    "github": "https://github.com/aegoroff/solv"
+  }
+}"#,
+        )
+    }
+
+    #[rstest]
+    fn new_scoop_all_architectures(root: VfsPath) {
+        // Arrange
+        let binary_path = root.join("x64").unwrap();
+        let binary32_path = root.join("x86").unwrap();
+        let binary_arm_path = root.join("arm64").unwrap();
+        for (path, file) in [
+            (&binary32_path, "solv_x86.tar.gz"),
+            (&binary_arm_path, "solv_arm64.tar.gz"),
+        ] {
+            path.create_dir().unwrap();
+            path.join(file)
+                .unwrap()
+                .create_file()
+                .unwrap()
+                .write_all("1234".as_bytes())
+                .unwrap();
+        }
+
+        // Act
+        let result = Scoop::serialize(
+            &root,
+            &binary_path,
+            Some(&binary32_path),
+            Some(&binary_arm_path),
+            "solv.exe",
+            "http://localhost",
+            ScoopConfig::default(),
+        );
+
+        // Assert
+        assert_eq!(
+            result.unwrap().as_str(),
+            r#"{
   "version": "0.1.13",
+  "description": "Microsoft Visual Studio solution parsing library",
+  "homepage": "https://github.com/aegoroff/solv",
   "license": "MIT",
   "architecture": {
     "64bit": {
@@ -87,19 +358,326 @@ mod tests {
       "bin": [
         "solv.exe"
       ]
+    },
+    "32bit": {
+      "url": "http://localhost/solv_x86.tar.gz",
+      "hash": "03ac674216f3e15c761ee1a5e255f067953623c8b388b4459e13f978d7c846f4",
+      "bin": [
+        "solv.exe"
+      ]
+    },
+    "arm64": {
+      "url": "http://localhost/solv_arm64.tar.gz",
+      "hash": "03ac674216f3e15c761ee1a5e255f067953623c8b388b4459e13f978d7c846f4",
+      "bin": [
+        "solv.exe"
+      ]
     }
+  },
+  "checkver": {
+    "github": "https://github.com/aegoroff/solv"
   }
 }"#,
         )
     }
 
+    #[rstest]
+    fn new_scoop_arm_path_not_exist(root: VfsPath) {
+        // Arrange
+        let binary_path = root.join("x64").unwrap();
+        let binary_arm_path = root.join("arm64").unwrap();
+
+        // Act
+        let result = Scoop::serialize(
+            &root,
+            &binary_path,
+            None,
+            Some(&binary_arm_path),
+            "solv.exe",
+            "http://localhost",
+            ScoopConfig::default(),
+        );
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    #[case::tag_and_file(
+        "https://github.com/aegoroff/solv/releases/download/v0.1.13/solv_0.1.13_x64.tar.gz",
+        Some(
+            "https://github.com/aegoroff/solv/releases/download/v$version/solv_$version_x64.tar.gz"
+        )
+    )]
+    #[case::extension(
+        "http://localhost/0.1.13/solv-0.1.13.tar.gz",
+        Some("http://localhost/$version/solv-$version.tar.gz")
+    )]
+    #[case::longer_version("http://localhost/0.1.130/solv.tar.gz", None)]
+    #[case::shorter_version("http://localhost/10.1.13.1/solv.tar.gz", None)]
+    #[case::no_version("http://localhost/solv.tar.gz", None)]
+    #[trace]
+    fn templatize_version_tests(#[case] url: &str, #[case] expected: Option<&str>) {
+        // Arrange
+
+        // Act
+        let actual = templatize_version(url, "0.1.13");
+
+        // Assert
+        assert_eq!(expected, actual.as_deref());
+    }
+
+    #[rstest]
+    fn new_scoop_autoupdate(root: VfsPath) {
+        // Arrange
+        let binary_path = root.join("x64").unwrap();
+
+        // Act
+        let result = Scoop::serialize(
+            &root,
+            &binary_path,
+            None,
+            None,
+            "solv.exe",
+            "https://github.com/aegoroff/solv/releases/download/v0.1.13",
+            ScoopConfig::default(),
+        );
+
+        // Assert
+        assert!(result.unwrap().ends_with(
+            r#"  "checkver": {
+    "github": "https://github.com/aegoroff/solv"
+  },
+  "autoupdate": {
+    "architecture": {
+      "64bit": {
+        "url": "https://github.com/aegoroff/solv/releases/download/v$version/solv.tar.gz"
+      }
+    }
+  }
+}"#
+        ));
+    }
+
+    #[rstest]
+    fn new_scoop_autoupdate_url_without_version(root: VfsPath) {
+        // Arrange
+        let binary_path = root.join("x64_versioned").unwrap();
+        binary_path.create_dir().unwrap();
+        binary_path
+            .join("solv_0.1.13_x64.tar.gz")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(b"123")
+            .unwrap();
+        let binary32_path = root.join("x86").unwrap();
+        binary32_path.create_dir().unwrap();
+        binary32_path
+            .join("solv_x86.tar.gz")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(b"1234")
+            .unwrap();
+
+        // Act
+        let result = Scoop::serialize(
+            &root,
+            &binary_path,
+            Some(&binary32_path),
+            None,
+            "solv.exe",
+            "http://localhost",
+            ScoopConfig::default(),
+        );
+
+        // Assert
+        let err = result.unwrap_err().to_string();
+        assert!(
+            err.contains("32bit package URL http://localhost/solv_x86.tar.gz"),
+            "{err}"
+        );
+    }
+
+    #[rstest]
+    fn new_scoop_extras(root: VfsPath) {
+        // Arrange
+        let binary_path = root.join("v0.1.13").unwrap();
+        binary_path.create_dir().unwrap();
+        binary_path
+            .join("solv.tar.gz")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(&tar_gz(&["solv-0.1.13/solv.exe", "solv-0.1.13/README.md"]))
+            .unwrap();
+        let config = ScoopConfig {
+            alias: Some("sv".to_string()),
+            shortcut: Some("Solution validator".to_string()),
+            persist: Some(vec!["config".to_string()]),
+            env_add_path: Some(vec!["bin".to_string()]),
+            depends: Some(vec!["git".to_string()]),
+            notes: Some(vec!["Run sv --help".to_string()]),
+            ..Default::default()
+        };
+
+        // Act
+        let result = Scoop::serialize(
+            &root,
+            &binary_path,
+            None,
+            None,
+            "solv.exe",
+            "http://localhost/v0.1.13",
+            config,
+        );
+
+        // Assert
+        let hash =
+            crate::hash::calculate_sha256(&binary_path.join("solv.tar.gz").unwrap()).unwrap();
+        assert_eq!(
+            format!(
+                r#"{{
+  "version": "0.1.13",
+  "description": "Microsoft Visual Studio solution parsing library",
+  "homepage": "https://github.com/aegoroff/solv",
+  "license": "MIT",
+  "notes": [
+    "Run sv --help"
+  ],
+  "depends": [
+    "git"
+  ],
+  "architecture": {{
+    "64bit": {{
+      "url": "http://localhost/v0.1.13/solv.tar.gz",
+      "hash": "{hash}",
+      "extract_dir": "solv-0.1.13",
+      "bin": [
+        [
+          "solv.exe",
+          "sv"
+        ]
+      ]
+    }}
+  }},
+  "shortcuts": [
+    [
+      "solv.exe",
+      "Solution validator"
+    ]
+  ],
+  "persist": [
+    "config"
+  ],
+  "env_add_path": [
+    "bin"
+  ],
+  "checkver": {{
+    "github": "https://github.com/aegoroff/solv"
+  }},
+  "autoupdate": {{
+    "architecture": {{
+      "64bit": {{
+        "url": "http://localhost/v$version/solv.tar.gz",
+        "extract_dir": "solv-$version"
+      }}
+    }}
+  }}
+}}"#
+            ),
+            result.unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case::manifest(ScoopConfig::default(), "tools")]
+    #[case::command_line(
+        ScoopConfig {
+            extract_dir: Some("bin".to_string()),
+            ..Default::default()
+        },
+        "bin"
+    )]
+    #[trace]
+    fn new_scoop_manifest_config(
+        root: VfsPath,
+        #[case] config: ScoopConfig,
+        #[case] extract_dir: &str,
+    ) {
+        // Arrange
+        let manifest = format!(
+            "{CONFIG}\n[package.metadata.releaser.scoop]\nextract-dir = \"tools\"\nalias = \"solv\"\n"
+        );
+        root.join(CARGO_CONFIG)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(manifest.as_bytes())
+            .unwrap();
+        let binary_path = root.join("x64").unwrap();
+
+        // Act
+        let result = Scoop::serialize(
+            &root,
+            &binary_path,
+            None,
+            None,
+            "solv.exe",
+            "http://localhost",
+            config,
+        );
+
+        // Assert
+        let result = result.unwrap();
+        assert!(result.contains(&format!("\"extract_dir\": \"{extract_dir}\"")));
+        // alias set in manifest isn't overridden
+        assert!(result.contains("[\n          \"solv.exe\",\n          \"solv\"\n        ]"));
+    }
+
+    #[test]
+    fn scoop_config_or_test() {
+        // Arrange
+        let config = ScoopConfig {
+            alias: Some("a".to_string()),
+            ..Default::default()
+        };
+        let other = ScoopConfig {
+            alias: Some("b".to_string()),
+            shortcut: Some("s".to_string()),
+            ..Default::default()
+        };
+
+        // Act
+        let actual = config.or(Some(&other));
+
+        // Assert
+        assert_eq!(
+            ScoopConfig {
+                alias: Some("a".to_string()),
+                shortcut: Some("s".to_string()),
+                ..Default::default()
+            },
+            actual
+        );
+    }
+
     #[rstest]
     fn new_scoop_binary_path_not_exist(root: VfsPath) {
         // Arrange
         let binary_path = root.join("x86").unwrap();
 
         // Act
-        let result = Scoop::serialize(&root, &binary_path, "solv.exe", "http://localhost");
+        let result = Scoop::serialize(
+            &root,
+            &binary_path,
+            None,
+            None,
+            "solv.exe",
+            "http://localhost",
+            ScoopConfig::default(),
+        );
 
         // Assert
         assert!(result.is_err());
@@ -129,7 +707,15 @@ mod tests {
             .unwrap();
 
         // Act
-        let result = Scoop::serialize(&root, &binary_path, "solv.exe", "http://localhost");
+        let result = Scoop::serialize(
+            &root,
+            &binary_path,
+            None,
+            None,
+            "solv.exe",
+            "http://localhost",
+            ScoopConfig::default(),
+        );
 
         // Assert
         assert!(result.is_err());
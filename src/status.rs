@@ -0,0 +1,225 @@
+use std::fmt::Write;
+
+use crate::version_iter::VersionIter;
+use crate::workflow::VPath;
+use crate::{CARGO_CONFIG, CrateConfig, Destination, Index, Result, Vcs};
+
+const HEADERS: [&str; 6] = [
+    "Crate",
+    "Version",
+    "Last tag",
+    "Commits since tag",
+    "Publishable",
+    "Published",
+];
+
+/// Release state of workspace member
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberStatus {
+    /// Crate name
+    pub name: String,
+    /// Version set in crate's manifest
+    pub version: String,
+    /// Most recent release tag. None if repository has no tags
+    pub last_tag: Option<String>,
+    /// Number of commits that changed crate's files since the last tag
+    pub commits_since_tag: u32,
+    /// Whether crate's publish setting allows publishing to the registry
+    pub publishable: bool,
+    /// Whether crate's version is in registry index. None if crate isn't
+    /// publishable or index cannot be queried
+    pub published: Option<bool>,
+}
+
+pub struct Status<V: Vcs, I: Index> {
+    vcs: V,
+    index: I,
+}
+
+impl<V: Vcs, I: Index> Status<V, I> {
+    pub fn new(vcs: V, index: I) -> Self {
+        Self { vcs, index }
+    }
+
+    /// Collects release state of workspace members in the order they're listed
+    /// in workspace config. Default registry (crates.io) used if registry isn't set
+    pub fn workspace(&self, root: &VPath<'_>, registry: Option<&str>) -> Result<Vec<MemberStatus>> {
+        let config = root.virtual_path.join(CARGO_CONFIG)?;
+        let it = VersionIter::open(&config)?;
+        let last_tag = self.vcs.last_tag(root.real_path)?;
+        let mut statuses = vec![];
+        for member in it.members() {
+            let conf = CrateConfig::open(&root.virtual_path.join(member)?.join(CARGO_CONFIG)?)?;
+            let (name, version) = (conf.package.name.as_str(), conf.package.version.as_str());
+            let member_path = format!("{}/{member}", root.real_path);
+            let commits_since_tag = self.vcs.commits_since(&member_path, last_tag.as_deref())?;
+            let (publishable, published) = match conf.package.destination(registry) {
+                Destination::Registry(r) => (true, self.index.contains(r, name, version).ok()),
+                Destination::Nowhere => (false, None),
+            };
+            statuses.push(MemberStatus {
+                name: name.to_string(),
+                version: version.to_string(),
+                last_tag: last_tag.clone(),
+                commits_since_tag,
+                publishable,
+                published,
+            });
+        }
+        Ok(statuses)
+    }
+}
+
+/// Formats statuses as a table with column per status field
+pub fn render(statuses: &[MemberStatus]) -> String {
+    let yes_no = |b: bool| if b { "yes" } else { "no" }.to_string();
+    let rows = statuses
+        .iter()
+        .map(|s| {
+            [
+                s.name.clone(),
+                s.version.clone(),
+                s.last_tag.clone().unwrap_or_else(|| "-".to_string()),
+                s.commits_since_tag.to_string(),
+                yes_no(s.publishable),
+                s.published.map_or_else(|| "-".to_string(), yes_no),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let widths = HEADERS.map(str::len);
+    let widths = rows.iter().fold(widths, |mut widths, row| {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.len());
+        }
+        widths
+    });
+    let headers = HEADERS.map(ToString::to_string);
+    let mut out = String::new();
+    for row in std::iter::once(&headers).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        let _ = writeln!(out, "{}", line.trim_end());
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_in_result)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::{MockIndex, MockVcs};
+    use mockall::predicate::eq;
+    use vfs::{MemoryFS, VfsPath};
+
+    #[test]
+    fn workspace_test() {
+        // Arrange
+        let root = VfsPath::new(MemoryFS::new());
+        write(
+            &root,
+            CARGO_CONFIG,
+            "[workspace]\nmembers = [\"a\", \"b\"]\n",
+        );
+        write(
+            &root,
+            "a/Cargo.toml",
+            "[package]\nname = \"a\"\nversion = \"0.1.0\"\n",
+        );
+        write(
+            &root,
+            "b/Cargo.toml",
+            "[package]\nname = \"b\"\nversion = \"0.2.0\"\npublish = false\n",
+        );
+        let mut vcs = MockVcs::new();
+        vcs.expect_last_tag()
+            .with(eq("/x"))
+            .returning(|_| Ok(Some("v0.1.0".to_string())));
+        vcs.expect_commits_since()
+            .withf(|p, t| p == "/x/a" && *t == Some("v0.1.0"))
+            .returning(|_, _| Ok(2));
+        vcs.expect_commits_since()
+            .withf(|p, t| p == "/x/b" && *t == Some("v0.1.0"))
+            .returning(|_, _| Ok(0));
+        let mut index = MockIndex::new();
+        index
+            .expect_contains()
+            .withf(|r, n, v| r.is_none() && n == "a" && v == "0.1.0")
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+        let status = Status::new(vcs, index);
+
+        // Act
+        let actual = status.workspace(&VPath::new("/x", root), None).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![
+                MemberStatus {
+                    name: "a".to_string(),
+                    version: "0.1.0".to_string(),
+                    last_tag: Some("v0.1.0".to_string()),
+                    commits_since_tag: 2,
+                    publishable: true,
+                    published: Some(true),
+                },
+                MemberStatus {
+                    name: "b".to_string(),
+                    version: "0.2.0".to_string(),
+                    last_tag: Some("v0.1.0".to_string()),
+                    commits_since_tag: 0,
+                    publishable: false,
+                    published: None,
+                },
+            ],
+            actual
+        );
+    }
+
+    #[test]
+    fn render_test() {
+        // Arrange
+        let statuses = [
+            MemberStatus {
+                name: "solp".to_string(),
+                version: "0.1.13".to_string(),
+                last_tag: None,
+                commits_since_tag: 12,
+                publishable: true,
+                published: Some(false),
+            },
+            MemberStatus {
+                name: "x".to_string(),
+                version: "1.0.0".to_string(),
+                last_tag: None,
+                commits_since_tag: 0,
+                publishable: false,
+                published: None,
+            },
+        ];
+
+        // Act
+        let actual = render(&statuses);
+
+        // Assert
+        assert_eq!(
+            "Crate  Version  Last tag  Commits since tag  Publishable  Published\n\
+             solp   0.1.13   -         12                 yes          no\n\
+             x      1.0.0    -         0                  no           -",
+            actual
+        );
+    }
+
+    fn write(root: &VfsPath, path: &str, content: &str) {
+        let file = root.join(path).unwrap();
+        file.parent().create_dir_all().unwrap();
+        file.create_file()
+            .unwrap()
+            .write_all(content.as_bytes())
+            .unwrap();
+    }
+}
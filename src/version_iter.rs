@@ -1,9 +1,8 @@
 use crate::{CARGO_CONFIG, CrateConfig, CrateVersion, Dependency, Place, VERSION, WorkspaceConfig};
-use color_eyre::eyre::Result;
+use crate::{Error, Result};
 use petgraph::algo::DfsSpace;
 use petgraph::graphmap::DiGraphMap;
 use std::collections::HashMap;
-use std::io::Read;
 use std::ops::Deref;
 use vfs::VfsPath;
 
@@ -16,10 +15,7 @@ pub struct VersionIter<'a> {
 
 impl<'a> VersionIter<'a> {
     pub fn open(path: &'a VfsPath) -> Result<Self> {
-        let mut wks_file = path.open_file()?;
-        let mut wc = String::new();
-        wks_file.read_to_string(&mut wc)?;
-        let wks: WorkspaceConfig = toml::from_str(&wc)?;
+        let wks = WorkspaceConfig::open(path)?;
         let search: HashMap<String, usize> = wks
             .workspace
             .members
@@ -30,7 +26,11 @@ impl<'a> VersionIter<'a> {
             .collect();
         let members = wks.workspace.members;
 
-        let graph = DiGraphMap::new();
+        // all members are nodes so that ones without dependencies between them are ordered too
+        let mut graph = DiGraphMap::new();
+        for i in 0..members.len() {
+            graph.add_node(i);
+        }
         Ok(Self {
             search,
             members,
@@ -39,7 +39,16 @@ impl<'a> VersionIter<'a> {
         })
     }
 
-    pub fn topo_sort(&self) -> Vec<String> {
+    /// Workspace members in the order they're listed in workspace config
+    pub fn members(&self) -> Vec<&String> {
+        let mut members = self.search.iter().collect::<Vec<(&String, &usize)>>();
+        members.sort_unstable_by_key(|(_, i)| **i);
+        members.into_iter().map(|(m, _)| m).collect()
+    }
+
+    /// Members in publish order i.e. dependencies first.
+    /// Fails listing members involved if there are dependency cycles
+    pub fn topo_sort(&self) -> Result<Vec<String>> {
         let reverted = self
             .search
             .iter()
@@ -47,17 +56,53 @@ impl<'a> VersionIter<'a> {
             .collect::<HashMap<usize, &String>>();
 
         let mut space = DfsSpace::new(&self.graph);
-        if let Ok(sorted) = petgraph::algo::toposort(&self.graph, Some(&mut space)) {
-            sorted
+        match petgraph::algo::toposort(&self.graph, Some(&mut space)) {
+            Ok(sorted) => Ok(sorted
                 .into_iter()
                 .filter_map(|g| reverted.get(&g))
                 .map(Deref::deref)
                 .cloned()
-                .collect()
-        } else {
-            vec![]
+                .collect()),
+            Err(_) => Err(Error::DependencyCycle(self.cycles().concat())),
         }
     }
+
+    /// Dependencies between members as (dependency, dependent) pairs
+    /// in the order members are listed in workspace config
+    pub fn dependencies(&self) -> Vec<(String, String)> {
+        let mut edges = self.graph.all_edges().collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges
+            .into_iter()
+            .map(|(from, to, ())| (self.name(from), self.name(to)))
+            .collect()
+    }
+
+    /// Groups of members that depend on each other so that they cannot be ordered.
+    /// Members inside group are in the order they're listed in workspace config
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let mut cycles = petgraph::algo::tarjan_scc(&self.graph)
+            .into_iter()
+            .filter(|c| c.len() > 1 || c.iter().any(|n| self.graph.contains_edge(*n, *n)))
+            .map(|mut c| {
+                c.sort_unstable();
+                c
+            })
+            .collect::<Vec<_>>();
+        cycles.sort_unstable();
+        cycles
+            .into_iter()
+            .map(|c| c.into_iter().map(|n| self.name(n)).collect())
+            .collect()
+    }
+
+    fn name(&self, node: usize) -> String {
+        self.search
+            .iter()
+            .find(|(_, i)| **i == node)
+            .map(|(m, _)| m.clone())
+            .unwrap_or_default()
+    }
 }
 
 impl Iterator for VersionIter<'_> {
@@ -74,7 +119,8 @@ impl Iterator for VersionIter<'_> {
         let mut item = conf.new_version(member);
 
         let deps = conf
-            .dependencies?
+            .dependencies
+            .unwrap_or_default()
             .into_iter()
             .filter(|(n, _)| self.search.contains_key(n))
             .filter_map(|(n, v)| {
@@ -172,7 +218,7 @@ mod tests {
         let actual = update_configs(&conf, &mut it, Increment::Minor);
 
         // Act
-        let sorted = it.topo_sort();
+        let sorted = it.topo_sort().unwrap();
 
         // Assert
         assert!(actual.is_ok());
@@ -274,8 +320,9 @@ a = { path = "../a/", version = "0.1.0" }
         assert_eq!("0.2.0", result.unwrap().to_string());
         assert_eq!(4, it.graph.node_count());
         assert_eq!(3, it.graph.edge_count());
-        let sorted = it.topo_sort();
+        let sorted = it.topo_sort().unwrap();
         assert_eq!(vec!["a", "d", "b", "c"], sorted);
+        assert_eq!(vec!["a", "b", "c", "d"], it.members());
     }
 
     #[fixture]
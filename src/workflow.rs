@@ -1,23 +1,29 @@
-use std::{thread, time};
+use std::fmt;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::{iter, thread};
 
-use console::style;
 use semver::Version;
-use vfs::VfsPath;
+use vfs::{MemoryFS, OverlayFS, VfsPath};
 
 use crate::CrateConfig;
 use crate::Increment;
 use crate::Publisher;
-use crate::Vcs;
+use crate::observer::{ConsoleObserver, ReleaseEvent, ReleaseObserver, SkipReason};
 use crate::version_iter::VersionIter;
+use crate::{CARGO_CONFIG, CARGO_LOCK, CommitOptions, DEFAULT_REMOTE, PushOptions, TagOptions};
+use crate::{Destination, Index, MemberPublishConfig, WorkspaceConfig};
+use crate::{Error, Result};
 use crate::{PublishOptions, new_cargo_config_path};
-use color_eyre::eyre::Result;
+use crate::{Vcs, VcsFeature};
 
 /// Represents virtual path in a filesystem
 /// that keeps real fs path that is root of this
 /// virtual path
 pub struct VPath<'a> {
-    real_path: &'a str,
-    virtual_path: VfsPath,
+    pub(crate) real_path: &'a str,
+    pub(crate) virtual_path: VfsPath,
 }
 
 impl<'a> VPath<'a> {
@@ -30,140 +36,1035 @@ impl<'a> VPath<'a> {
     }
 }
 
+/// Version control settings used while releasing
+#[derive(Debug, Clone)]
+pub struct VcsOptions {
+    /// Release tag annotation message (release notes for example).
+    /// Lightweight tag created if not set and tag signing not requested
+    pub tag_message: Option<String>,
+    /// Whether to sign release tag
+    pub sign_tag: bool,
+    /// Whether to sign version bump commit
+    pub sign_commit: bool,
+    /// Remote to push changes and tag to
+    pub remote: String,
+    /// Target branch on remote. Current branch pushed if not set
+    pub branch: Option<String>,
+    /// Whether to push branch and tag atomically i.e. both or none of them
+    pub atomic: bool,
+    /// Additional files (relative to root) to include into version change commit (changelog for example)
+    pub files: Vec<String>,
+    /// Whether to allow release from working tree that has uncommitted changes
+    pub allow_dirty_tree: bool,
+}
+
+impl Default for VcsOptions {
+    fn default() -> Self {
+        Self {
+            tag_message: None,
+            sign_tag: false,
+            sign_commit: false,
+            remote: DEFAULT_REMOTE.to_string(),
+            branch: None,
+            atomic: false,
+            files: vec![],
+            allow_dirty_tree: false,
+        }
+    }
+}
+
+impl VcsOptions {
+    fn push_options(&self) -> PushOptions<'_> {
+        PushOptions {
+            remote: &self.remote,
+            branch: self.branch.as_deref(),
+        }
+    }
+}
+
+/// Publish settings used while releasing. Workspace members can override them
+/// in `[workspace.metadata.releaser.members.<member>]` section of workspace's Cargo.toml
+#[derive(Default, Debug, Clone)]
+pub struct PublishSettings {
+    /// Registry to publish to i.e. --registry option of cargo publish. crates.io used if not set
+    pub registry: Option<String>,
+    /// Whether to publish all features i.e. pass --all-features flag to cargo publish
+    pub all_features: bool,
+    /// Whether to skip package tarball verification i.e. pass --no-verify flag to cargo publish
+    pub no_verify: bool,
+    /// Features to activate i.e. --features option of cargo publish
+    pub features: Vec<String>,
+    /// Whether to pass --no-default-features flag to cargo publish
+    pub no_default_features: bool,
+    /// Target triple to build package for i.e. --target option of cargo publish
+    pub target: Option<String>,
+    /// Whether to pass --locked flag to cargo publish
+    pub locked: bool,
+    /// Number of parallel build jobs i.e. --jobs option of cargo publish
+    pub jobs: Option<u16>,
+    /// Whether to pass --allow-dirty flag to cargo publish
+    pub allow_dirty: bool,
+}
+
+impl PublishSettings {
+    /// Creates member's settings applying overrides from workspace metadata
+    fn with_member_config(&self, member: &MemberPublishConfig) -> Self {
+        Self {
+            registry: self.registry.clone(),
+            all_features: member.all_features.unwrap_or(self.all_features),
+            no_verify: member.no_verify.unwrap_or(self.no_verify),
+            features: member
+                .features
+                .clone()
+                .unwrap_or_else(|| self.features.clone()),
+            no_default_features: member
+                .no_default_features
+                .unwrap_or(self.no_default_features),
+            target: member.target.clone().or_else(|| self.target.clone()),
+            locked: member.locked.unwrap_or(self.locked),
+            jobs: member.jobs.or(self.jobs),
+            allow_dirty: member.allow_dirty.unwrap_or(self.allow_dirty),
+        }
+    }
+
+    fn options<'a>(
+        &'a self,
+        crate_to_publish: Option<&'a str>,
+        registry: Option<&'a str>,
+    ) -> PublishOptions<'a> {
+        PublishOptions {
+            crate_to_publish,
+            registry,
+            all_features: self.all_features,
+            no_verify: self.no_verify,
+            features: &self.features,
+            no_default_features: self.no_default_features,
+            target: self.target.as_deref(),
+            locked: self.locked,
+            jobs: self.jobs,
+            allow_dirty: self.allow_dirty,
+        }
+    }
+
+    /// Options of packaging verification. Working tree is always dirty at this point because
+    /// manifests with new version aren't committed yet while preflight ensured there are no other changes
+    fn package_options<'a>(&'a self, registry: Option<&'a str>) -> PublishOptions<'a> {
+        PublishOptions {
+            allow_dirty: true,
+            ..self.options(None, registry)
+        }
+    }
+}
+
+/// Publish retry settings. Only transient failures (registry rate limit, network errors etc.) retried
+#[derive(Debug, Clone, Copy)]
+pub struct RetrySettings {
+    /// Max number of retries after the first failed attempt
+    pub retries: u32,
+    /// Delay in seconds before the first retry. Doubled before every next retry
+    pub backoff_seconds: u64,
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            backoff_seconds: 10,
+        }
+    }
+}
+
+/// Shell commands run at release stages. Commands run in root directory
+/// with `RELEASER_VERSION` environment variable set to the new version
+#[derive(Default, Debug, Clone)]
+pub struct Hooks {
+    /// Commands run after versions updated but before version change commit (changelog generation for example)
+    pub before_commit: Vec<String>,
+    /// Commands run after release tag pushed
+    pub after_release: Vec<String>,
+}
+
+/// Release settings. Created using [`ReleaseOptions::builder`]
+#[derive(Clone)]
+pub struct ReleaseOptions {
+    increment: Increment,
+    publish: PublishSettings,
+    vcs: VcsOptions,
+    delay_seconds: u64,
+    dry_run: bool,
+    hooks: Hooks,
+    retry: RetrySettings,
+    verify_packages: bool,
+    observer: Arc<dyn ReleaseObserver + Send + Sync>,
+}
+
+impl ReleaseOptions {
+    /// Creates builder of options with increment specified and default settings
+    /// i.e. publish to crates.io, push to origin, no delay between crates and no hooks
+    #[must_use]
+    pub fn builder(increment: Increment) -> ReleaseOptionsBuilder {
+        ReleaseOptionsBuilder {
+            options: Self {
+                increment,
+                publish: PublishSettings::default(),
+                vcs: VcsOptions::default(),
+                delay_seconds: 0,
+                dry_run: false,
+                hooks: Hooks::default(),
+                retry: RetrySettings::default(),
+                verify_packages: true,
+                observer: Arc::new(ConsoleObserver),
+            },
+        }
+    }
+
+    /// Version increment (major, minor or patch)
+    #[must_use]
+    pub fn increment(&self) -> Increment {
+        self.increment
+    }
+
+    /// cargo publish settings
+    #[must_use]
+    pub fn publish(&self) -> &PublishSettings {
+        &self.publish
+    }
+
+    /// Commit, tag and push settings
+    #[must_use]
+    pub fn vcs(&self) -> &VcsOptions {
+        &self.vcs
+    }
+
+    /// Max number of seconds to wait for published workspace member to appear in registry index
+    #[must_use]
+    pub fn delay_seconds(&self) -> u64 {
+        self.delay_seconds
+    }
+
+    /// Whether to only show what would be done without changing anything
+    #[must_use]
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Commands run at release stages
+    #[must_use]
+    pub fn hooks(&self) -> &Hooks {
+        &self.hooks
+    }
+
+    /// Publish retry settings
+    #[must_use]
+    pub fn retry(&self) -> RetrySettings {
+        self.retry
+    }
+
+    /// Whether to package and verify all crates before version change commit
+    #[must_use]
+    pub fn verify_packages(&self) -> bool {
+        self.verify_packages
+    }
+
+    fn notify(&self, event: &ReleaseEvent<'_>) {
+        self.observer.on_event(event, self.dry_run);
+    }
+}
+
+impl fmt::Debug for ReleaseOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReleaseOptions")
+            .field("increment", &self.increment)
+            .field("publish", &self.publish)
+            .field("vcs", &self.vcs)
+            .field("delay_seconds", &self.delay_seconds)
+            .field("dry_run", &self.dry_run)
+            .field("hooks", &self.hooks)
+            .field("retry", &self.retry)
+            .field("verify_packages", &self.verify_packages)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builder of [`ReleaseOptions`]
+#[derive(Debug, Clone)]
+pub struct ReleaseOptionsBuilder {
+    options: ReleaseOptions,
+}
+
+impl ReleaseOptionsBuilder {
+    #[must_use]
+    pub fn publish(mut self, publish: PublishSettings) -> Self {
+        self.options.publish = publish;
+        self
+    }
+
+    #[must_use]
+    pub fn vcs(mut self, vcs: VcsOptions) -> Self {
+        self.options.vcs = vcs;
+        self
+    }
+
+    #[must_use]
+    pub fn delay_seconds(mut self, delay_seconds: u64) -> Self {
+        self.options.delay_seconds = delay_seconds;
+        self
+    }
+
+    #[must_use]
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.options.dry_run = dry_run;
+        self
+    }
+
+    #[must_use]
+    pub fn hooks(mut self, hooks: Hooks) -> Self {
+        self.options.hooks = hooks;
+        self
+    }
+
+    #[must_use]
+    pub fn retry(mut self, retry: RetrySettings) -> Self {
+        self.options.retry = retry;
+        self
+    }
+
+    #[must_use]
+    pub fn verify_packages(mut self, verify_packages: bool) -> Self {
+        self.options.verify_packages = verify_packages;
+        self
+    }
+
+    /// Sets observer that receives release progress events instead of console one
+    #[must_use]
+    pub fn observer(mut self, observer: Arc<dyn ReleaseObserver + Send + Sync>) -> Self {
+        self.options.observer = observer;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> ReleaseOptions {
+        self.options
+    }
+}
+
 pub trait Release<'a> {
     /// Releases crate or workspace
     /// * `root` - path to folder where crate's or workspace's Cargo.toml located
-    /// * `incr` - Version increment (major, minor or patch)
-    /// * `all_features` - whether to publish all features i.e. pass --all-features flag to cargo publish
-    /// * `no_verify` - whether to verify package tarball before publish i.e. pass --no-verify flag to cargo publish
-    fn release(
-        &self,
-        root: VPath<'a>,
-        incr: Increment,
-        all_features: bool,
-        no_verify: bool,
-    ) -> Result<()>;
+    /// * `options` - release settings
+    fn release(&self, root: VPath<'a>, options: &ReleaseOptions) -> Result<()>;
 }
 
-pub struct Workspace<P: Publisher, V: Vcs> {
-    delay_seconds: u64,
+/// Environment variable with new version passed to hook commands
+const VERSION_VARIABLE: &str = "RELEASER_VERSION";
+
+/// Interval between registry index checks while waiting for published crate
+const INDEX_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Workspace member to publish
+struct PlannedCrate {
+    /// Member path relative to workspace root
+    member: String,
+    name: String,
+    version: String,
+    registry: Option<String>,
+    settings: PublishSettings,
+}
+
+pub struct Workspace<P: Publisher, V: Vcs, I: Index> {
     publisher: P,
     vcs: V,
+    index: I,
 }
 
-impl<P: Publisher, V: Vcs> Workspace<P, V> {
-    pub fn new(delay_seconds: u64, publisher: P, vcs: V) -> Self {
+impl<P: Publisher, V: Vcs, I: Index> Workspace<P, V, I> {
+    pub fn new(publisher: P, vcs: V, index: I) -> Self {
         Self {
-            delay_seconds,
             publisher,
             vcs,
+            index,
         }
     }
-}
 
-impl<'a, P: Publisher, V: Vcs> Release<'a> for Workspace<P, V> {
-    fn release(
+    fn run(
         &self,
-        root: VPath<'a>,
-        incr: Increment,
-        all_features: bool,
-        no_verify: bool,
+        root: &VPath<'_>,
+        options: &ReleaseOptions,
+        publisher: &impl Publisher,
+        vcs: &impl Vcs,
     ) -> Result<()> {
+        preflight(vcs, root.real_path, &options.vcs)?;
         let crate_conf = new_cargo_config_path(&root.virtual_path)?;
 
+        // dependencies known only after all manifests read so cycles
+        // are detected by separate pass before anything changed
+        let mut check = VersionIter::open(&crate_conf)?;
+        check.by_ref().for_each(drop);
+        check.topo_sort()?;
+
         let mut it = VersionIter::open(&crate_conf)?;
-        let version = crate::update_configs(&crate_conf, &mut it, incr)?;
+        let manifests = it
+            .members()
+            .into_iter()
+            .map(|m| format!("{m}/{CARGO_CONFIG}"))
+            .collect::<Vec<String>>();
+        let snapshot = Snapshot::take(&root.virtual_path, manifests.iter().cloned())?;
+        let mut change = || {
+            let version = crate::update_configs(&crate_conf, &mut it, options.increment)?;
+            options.notify(&ReleaseEvent::VersionComputed { version: &version });
+            for path in &manifests {
+                options.notify(&ReleaseEvent::FileRewritten { path });
+            }
 
-        let ver = commit_version(&self.vcs, root.real_path, &version)?;
+            let plan = self.plan(&root.virtual_path, &crate_conf, &it.topo_sort()?, options)?;
+            if options.verify_packages && !plan.is_empty() {
+                let (settings, registry) = package_settings(&plan, &options.publish)?;
+                let crates = plan
+                    .iter()
+                    .map(|planned| planned.name.clone())
+                    .collect::<Vec<String>>();
+                verify_packages(
+                    publisher,
+                    root.real_path,
+                    &crates,
+                    settings.package_options(registry.as_deref()),
+                    options,
+                )?;
+            }
 
-        let delay_str = format!("{}", self.delay_seconds);
-        let delay = time::Duration::from_secs(self.delay_seconds);
-        let crates_to_publish = it.topo_sort();
-        for (i, publish) in crates_to_publish.iter().enumerate() {
-            let options = PublishOptions {
-                crate_to_publish: Some(publish),
-                all_features,
-                no_verify,
+            run_hooks(
+                &options.hooks.before_commit,
+                root.real_path,
+                &version,
+                options,
+            )?;
+            let files = release_files(
+                &root.virtual_path,
+                manifests.iter().cloned(),
+                &options.vcs.files,
+            )?;
+            let ver = commit_version(vcs, root.real_path, &version, &files, options)?;
+            Ok((version, plan, ver))
+        };
+        let (version, plan, ver) = snapshot.revert_on_error(change(), options)?;
+
+        for (i, planned) in plan.iter().enumerate() {
+            let registry = planned.registry.as_deref();
+            let publish_options = planned.settings.options(Some(&planned.member), registry);
+            publish_with_retry(
+                publisher,
+                &self.index,
+                root.real_path,
+                &planned.name,
+                &planned.version,
+                publish_options,
+                options,
+            )?;
+            // waiting between crates needed to avoid publish failure
+            // because registry index isn't updated instantly
+            if i < plan.len() - 1 && !options.dry_run {
+                self.wait_for_index(options, registry, &planned.name, &planned.version);
+            }
+        }
+
+        push_release(vcs, root.real_path, &ver, options)?;
+        run_hooks(
+            &options.hooks.after_release,
+            root.real_path,
+            &version,
+            options,
+        )
+    }
+
+    /// Selects members to publish in publish order. Members which publish setting doesn't allow
+    /// the registry and members which new version is already published are skipped
+    fn plan(
+        &self,
+        root: &VfsPath,
+        crate_conf: &VfsPath,
+        members: &[String],
+        options: &ReleaseOptions,
+    ) -> Result<Vec<PlannedCrate>> {
+        let workspace_conf = WorkspaceConfig::open(crate_conf)?;
+        let publish = &options.publish;
+        let registry = publish.registry.as_deref();
+        let mut plan = vec![];
+        for member in members {
+            let member_conf = root.join(member)?.join(CARGO_CONFIG)?;
+            let conf = CrateConfig::open(&member_conf)?;
+            let (name, version) = (&conf.package.name, &conf.package.version);
+            let Destination::Registry(registry) = conf.package.destination(registry) else {
+                options.notify(&ReleaseEvent::Skipped {
+                    name,
+                    version,
+                    registry,
+                    reason: SkipReason::NotAllowed,
+                });
+                continue;
             };
-            self.publisher.publish(root.real_path, options)?;
-            // delay between crates needed to avoid publish failure
-            // because crates.io index aren't updated instantly
-            if i < crates_to_publish.len() - 1 {
-                println!(
-                    " Waiting {} seconds after publish {} ...",
-                    style(&delay_str).green().bold(),
-                    style(publish).green().bold()
-                );
-                thread::sleep(delay);
+            if is_published(&self.index, registry, name, version) {
+                options.notify(&ReleaseEvent::Skipped {
+                    name,
+                    version,
+                    registry,
+                    reason: SkipReason::AlreadyPublished,
+                });
+                continue;
             }
+            let settings = workspace_conf
+                .member_publish_config(member)
+                .map_or_else(|| publish.clone(), |m| publish.with_member_config(m));
+            plan.push(PlannedCrate {
+                member: member.clone(),
+                name: name.clone(),
+                version: version.clone(),
+                registry: registry.map(ToString::to_string),
+                settings,
+            });
         }
+        Ok(plan)
+    }
 
-        self.vcs.push(root.real_path)?;
-        self.vcs.create_tag(root.real_path, &ver)?;
-        self.vcs.push_tag(root.real_path, &ver)?;
+    /// Waits until crate's version published appears in registry index but no longer than delay set.
+    /// Doesn't wait if index doesn't know published versions (nothing is published actually).
+    /// Just sleeps delay if index query fails
+    fn wait_for_index(
+        &self,
+        options: &ReleaseOptions,
+        registry: Option<&str>,
+        name: &str,
+        version: &str,
+    ) {
+        let delay_seconds = options.delay_seconds;
+        if delay_seconds == 0 || !self.index.is_queryable() {
+            return;
+        }
+        let delay = Duration::from_secs(delay_seconds);
+        options.notify(&ReleaseEvent::Waiting {
+            name,
+            version,
+            delay_seconds,
+        });
+        let started = Instant::now();
+        loop {
+            match self.index.contains(registry, name, version) {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(_) => {
+                    thread::sleep(delay.saturating_sub(started.elapsed()));
+                    return;
+                }
+            }
+            let remain = delay.saturating_sub(started.elapsed());
+            if remain.is_zero() {
+                return;
+            }
+            thread::sleep(INDEX_POLL_INTERVAL.min(remain));
+        }
+    }
+}
 
-        Ok(())
+impl<'a, P: Publisher, V: Vcs, I: Index> Release<'a> for Workspace<P, V, I> {
+    fn release(&self, root: VPath<'a>, options: &ReleaseOptions) -> Result<()> {
+        let result = if options.dry_run {
+            let root = dry_run_root(root);
+            self.run(&root, options, &DryRunPublisher, &DryRunVcs(&self.vcs))
+        } else {
+            self.run(&root, options, &self.publisher, &self.vcs)
+        };
+        notify_failure(result, options)
     }
 }
 
 #[derive(Default)]
-pub struct Crate<P: Publisher, V: Vcs> {
+pub struct Crate<P: Publisher, V: Vcs, I: Index> {
     publisher: P,
     vcs: V,
+    index: I,
 }
 
-impl<P: Publisher, V: Vcs> Crate<P, V> {
-    pub fn new(publisher: P, vcs: V) -> Self {
-        Self { publisher, vcs }
+impl<P: Publisher, V: Vcs, I: Index> Crate<P, V, I> {
+    pub fn new(publisher: P, vcs: V, index: I) -> Self {
+        Self {
+            publisher,
+            vcs,
+            index,
+        }
     }
-}
 
-impl<'a, P: Publisher, V: Vcs> Release<'a> for Crate<P, V> {
-    fn release(
+    fn run(
         &self,
-        root: VPath<'a>,
-        incr: Increment,
-        all_features: bool,
-        no_verify: bool,
+        root: &VPath<'_>,
+        options: &ReleaseOptions,
+        publisher: &impl Publisher,
+        vcs: &impl Vcs,
     ) -> Result<()> {
+        preflight(vcs, root.real_path, &options.vcs)?;
         let crate_conf = new_cargo_config_path(&root.virtual_path)?;
 
         let conf = CrateConfig::open(&crate_conf)?;
         let ver = conf.new_version(String::new());
-        let version = crate::update_config(&crate_conf, &ver, incr)?;
+        let snapshot = Snapshot::take(&root.virtual_path, iter::once(CARGO_CONFIG.to_string()))?;
+        let publish = &options.publish;
+        let name = &conf.package.name;
+        let change = || {
+            let version = crate::update_config(&crate_conf, &ver, options.increment)?;
+            options.notify(&ReleaseEvent::VersionComputed { version: &version });
+            options.notify(&ReleaseEvent::FileRewritten { path: CARGO_CONFIG });
 
-        let ver = commit_version(&self.vcs, root.real_path, &version)?;
+            // None if crate won't be published
+            let registry = publish.registry.as_deref();
+            let new_version = version.to_string();
+            let skipped = |registry, reason| ReleaseEvent::Skipped {
+                name,
+                version: &new_version,
+                registry,
+                reason,
+            };
+            let destination = match conf.package.destination(registry) {
+                Destination::Registry(registry)
+                    if is_published(&self.index, registry, name, &new_version) =>
+                {
+                    options.notify(&skipped(registry, SkipReason::AlreadyPublished));
+                    None
+                }
+                Destination::Registry(registry) => Some(registry),
+                Destination::Nowhere => {
+                    options.notify(&skipped(registry, SkipReason::NotAllowed));
+                    None
+                }
+            };
+            if let Some(registry) = destination
+                && options.verify_packages
+            {
+                let package_options = publish.package_options(registry);
+                verify_packages(publisher, root.real_path, &[], package_options, options)?;
+            }
 
-        let options = PublishOptions {
-            crate_to_publish: None,
-            all_features,
-            no_verify,
+            run_hooks(
+                &options.hooks.before_commit,
+                root.real_path,
+                &version,
+                options,
+            )?;
+            let manifests = iter::once(CARGO_CONFIG.to_string());
+            let files = release_files(&root.virtual_path, manifests, &options.vcs.files)?;
+            let ver = commit_version(vcs, root.real_path, &version, &files, options)?;
+            Ok((version, destination, ver))
+        };
+        let (version, destination, ver) = snapshot.revert_on_error(change(), options)?;
+
+        if let Some(registry) = destination {
+            let publish_options = publish.options(None, registry);
+            publish_with_retry(
+                publisher,
+                &self.index,
+                root.real_path,
+                name,
+                &version.to_string(),
+                publish_options,
+                options,
+            )?;
+        }
+
+        push_release(vcs, root.real_path, &ver, options)?;
+        run_hooks(
+            &options.hooks.after_release,
+            root.real_path,
+            &version,
+            options,
+        )
+    }
+}
+
+impl<'a, P: Publisher, V: Vcs, I: Index> Release<'a> for Crate<P, V, I> {
+    fn release(&self, root: VPath<'a>, options: &ReleaseOptions) -> Result<()> {
+        let result = if options.dry_run {
+            let root = dry_run_root(root);
+            self.run(&root, options, &DryRunPublisher, &DryRunVcs(&self.vcs))
+        } else {
+            self.run(&root, options, &self.publisher, &self.vcs)
+        };
+        notify_failure(result, options)
+    }
+}
+
+fn notify_failure(result: Result<()>, options: &ReleaseOptions) -> Result<()> {
+    if let Err(e) = &result {
+        options.notify(&ReleaseEvent::Failed {
+            error: &e.to_string(),
+        });
+    }
+    result
+}
+
+/// Root which changes are kept in memory so that real files stay intact
+fn dry_run_root(root: VPath<'_>) -> VPath<'_> {
+    let layers = [MemoryFS::new().into(), root.virtual_path];
+    VPath::new(root.real_path, OverlayFS::new(&layers).into())
+}
+
+/// Publisher that publishes nothing. Steps that would be made reported to observer
+struct DryRunPublisher;
+
+impl Publisher for DryRunPublisher {
+    fn publish<'a>(&'a self, _path: &'a str, _options: PublishOptions<'a>) -> Result<()> {
+        Ok(())
+    }
+
+    fn package<'a>(
+        &'a self,
+        _path: &'a str,
+        _crates: &'a [String],
+        _options: PublishOptions<'a>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Version control that changes nothing. Steps that would be made reported to observer.
+/// Working tree state queried from the real one
+struct DryRunVcs<'v, V: Vcs>(&'v V);
+
+impl<V: Vcs> Vcs for DryRunVcs<'_, V> {
+    fn commit<'a>(&'a self, _path: &'a str, _options: CommitOptions<'a>) -> Result<()> {
+        Ok(())
+    }
+
+    fn create_tag<'a>(
+        &'a self,
+        _path: &'a str,
+        _tag: &'a str,
+        _options: TagOptions<'a>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn push_tag<'a>(
+        &'a self,
+        _path: &'a str,
+        _tag: &'a str,
+        _options: PushOptions<'a>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn push<'a>(&'a self, _path: &'a str, _options: PushOptions<'a>) -> Result<()> {
+        Ok(())
+    }
+
+    fn push_atomic<'a>(
+        &'a self,
+        _path: &'a str,
+        _tag: &'a str,
+        _options: PushOptions<'a>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn is_clean(&self, path: &str) -> Result<bool> {
+        self.0.is_clean(path)
+    }
+
+    fn last_tag(&self, path: &str) -> Result<Option<String>> {
+        self.0.last_tag(path)
+    }
+
+    fn commits_since<'a>(&'a self, path: &'a str, tag: Option<&'a str>) -> Result<u32> {
+        self.0.commits_since(path, tag)
+    }
+
+    fn supports(&self, feature: VcsFeature) -> bool {
+        self.0.supports(feature)
+    }
+}
+
+/// Publishes crate retrying transient failures with exponential backoff.
+/// Transient failure may happen after upload succeeded so crate considered published
+/// without retry if its version appeared in registry index
+fn publish_with_retry(
+    publisher: &impl Publisher,
+    index: &impl Index,
+    path: &str,
+    name: &str,
+    version: &str,
+    publish_options: PublishOptions<'_>,
+    options: &ReleaseOptions,
+) -> Result<()> {
+    let registry = publish_options.registry;
+    options.notify(&ReleaseEvent::Publishing { name, registry });
+    let retry = options.retry;
+    let mut attempt = 0;
+    loop {
+        let Err(e) = publisher.publish(path, publish_options) else {
+            options.notify(&ReleaseEvent::Published { name, registry });
+            return Ok(());
+        };
+        let transient = matches!(&e, Error::Publish(p) if p.is_transient());
+        if !transient || attempt >= retry.retries {
+            return Err(e);
+        }
+        let delay = retry
+            .backoff_seconds
+            .saturating_mul(2u64.saturating_pow(attempt));
+        attempt += 1;
+        options.notify(&ReleaseEvent::Retrying {
+            error: &e.to_string(),
+            delay_seconds: delay,
+            attempt,
+            retries: retry.retries,
+        });
+        thread::sleep(Duration::from_secs(delay));
+        if is_published(index, registry, name, version) {
+            options.notify(&ReleaseEvent::Published { name, registry });
+            return Ok(());
+        }
+    }
+}
+
+/// Runs hook commands one by one. Release stops on the first failed command
+fn run_hooks(
+    commands: &[String],
+    path: &str,
+    version: &Version,
+    options: &ReleaseOptions,
+) -> Result<()> {
+    for command in commands {
+        options.notify(&ReleaseEvent::HookRunning { command });
+        if options.dry_run {
+            continue;
+        }
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
         };
-        self.publisher.publish(root.real_path, options)?;
+        let status = Command::new(shell)
+            .args([flag, command])
+            .current_dir(path)
+            .env(VERSION_VARIABLE, version.to_string())
+            .status()?;
+        if !status.success() {
+            return Err(Error::Hook(format!("Hook {command} failed with {status}")));
+        }
+    }
+    Ok(())
+}
+
+/// Whether crate's version is already in registry index i.e. published
+/// by previous (failed or interrupted) release. Unknown if index cannot be queried
+/// so publish is attempted in this case
+fn is_published(index: &impl Index, registry: Option<&str>, name: &str, version: &str) -> bool {
+    index.contains(registry, name, version).unwrap_or(false)
+}
+
+/// Checks that release can be started i.e. version control backend supports features requested
+/// and working tree is clean unless allowed otherwise. Must be called before any change made
+fn preflight(vcs: &impl Vcs, path: &str, vcs_options: &VcsOptions) -> Result<()> {
+    let requested = [
+        (vcs_options.sign_commit, VcsFeature::SignCommit),
+        (vcs_options.sign_tag, VcsFeature::SignTag),
+        (vcs_options.atomic, VcsFeature::AtomicPush),
+    ];
+    for (_, feature) in requested.into_iter().filter(|(on, _)| *on) {
+        if !vcs.supports(feature) {
+            return Err(Error::vcs(format!(
+                "{feature} isn't supported by version control backend selected"
+            )));
+        }
+    }
+    if !vcs_options.allow_dirty_tree && !vcs.is_clean(path)? {
+        return Err(Error::vcs(format!(
+            "Working tree at {path} has uncommitted changes. Commit or stash them before release"
+        )));
+    }
+    Ok(())
+}
+
+/// Collects files changed by release that have to be committed.
+/// Files that don't exist (Cargo.lock for example) are skipped
+fn release_files(
+    root: &VfsPath,
+    manifests: impl Iterator<Item = String>,
+    extra: &[String],
+) -> Result<Vec<String>> {
+    let mut files = vec![];
+    let candidates = manifests
+        .chain(iter::once(CARGO_LOCK.to_string()))
+        .chain(extra.iter().cloned());
+    for file in candidates {
+        if root.join(&file)?.exists()? {
+            files.push(file);
+        }
+    }
+    Ok(files)
+}
+
+/// Original content of manifests and Cargo.lock so that version change
+/// can be reverted if release aborted before commit
+struct Snapshot(Vec<(VfsPath, String)>);
 
-        self.vcs.push(root.real_path)?;
-        self.vcs.create_tag(root.real_path, &ver)?;
-        self.vcs.push_tag(root.real_path, &ver)?;
+impl Snapshot {
+    fn take(root: &VfsPath, manifests: impl Iterator<Item = String>) -> Result<Self> {
+        let mut files = vec![];
+        for file in release_files(root, manifests, &[])? {
+            let path = root.join(&file)?;
+            let content = path.read_to_string()?;
+            files.push((path, content));
+        }
+        Ok(Self(files))
+    }
 
+    fn restore(&self) -> Result<()> {
+        for (path, content) in &self.0 {
+            path.create_file()?.write_all(content.as_bytes())?;
+        }
         Ok(())
     }
+
+    /// Restores original content if release failed before version change committed
+    fn revert_on_error<T>(&self, result: Result<T>, options: &ReleaseOptions) -> Result<T> {
+        if result.is_err() {
+            self.restore()?;
+            options.notify(&ReleaseEvent::Reverted);
+        }
+        result
+    }
+}
+
+/// Resolves settings of packaging all planned crates by single cargo package call
+/// so that their dependencies on each other resolved. Member features qualified by crate name
+/// while settings that cargo cannot apply per package (registry, target, all-features
+/// and no-default-features) must be the same for all crates
+fn package_settings(
+    plan: &[PlannedCrate],
+    publish: &PublishSettings,
+) -> Result<(PublishSettings, Option<String>)> {
+    let mut settings = PublishSettings {
+        features: vec![],
+        no_verify: true,
+        locked: false,
+        ..publish.clone()
+    };
+    let mut registry = None;
+    for (i, planned) in plan.iter().enumerate() {
+        let member = &planned.settings;
+        if i == 0 {
+            registry.clone_from(&planned.registry);
+            settings.all_features = member.all_features;
+            settings.no_default_features = member.no_default_features;
+            settings.target.clone_from(&member.target);
+        }
+        let differs = [
+            ("registry", planned.registry != registry),
+            ("all-features", member.all_features != settings.all_features),
+            (
+                "no-default-features",
+                member.no_default_features != settings.no_default_features,
+            ),
+            ("target", member.target != settings.target),
+        ];
+        if let Some((setting, _)) = differs.iter().find(|(_, differs)| *differs) {
+            return Err(Error::packaging(format!(
+                "Crate {} has {setting} setting different from other crates so crates cannot be packaged together. Make setting the same or skip packaging verification",
+                planned.name
+            )));
+        }
+        settings.features.extend(
+            member
+                .features
+                .iter()
+                .map(|feature| format!("{}/{feature}", planned.name)),
+        );
+        // crate verified unless all of them opted out
+        settings.no_verify &= member.no_verify;
+        settings.locked |= member.locked;
+    }
+    Ok((settings, registry))
+}
+
+/// Packages and verifies crates before version change commit so that
+/// release aborted before anything published if any crate cannot be built
+fn verify_packages(
+    publisher: &impl Publisher,
+    path: &str,
+    crates: &[String],
+    package_options: PublishOptions<'_>,
+    options: &ReleaseOptions,
+) -> Result<()> {
+    options.notify(&ReleaseEvent::Packaging { crates });
+    publisher
+        .package(path, crates, package_options)
+        .map_err(|e| Error::Packaging {
+            message: "Packaging verification failed".to_string(),
+            source: Some(Box::new(e)),
+        })
 }
 
-fn commit_version(vcs: &impl Vcs, path: &str, version: &Version) -> Result<String> {
+fn commit_version(
+    vcs: &impl Vcs,
+    path: &str,
+    version: &Version,
+    files: &[String],
+    options: &ReleaseOptions,
+) -> Result<String> {
     let ver = format!("v{version}");
     let commit_msg = format!("changelog: {ver}");
-    vcs.commit(path, &commit_msg)?;
+    let commit_options = CommitOptions {
+        message: &commit_msg,
+        files,
+        sign: options.vcs.sign_commit,
+    };
+    vcs.commit(path, commit_options)?;
+    options.notify(&ReleaseEvent::Committed {
+        message: &commit_msg,
+        files,
+    });
     Ok(ver)
 }
 
+fn create_tag(vcs: &impl Vcs, path: &str, tag: &str, options: &ReleaseOptions) -> Result<()> {
+    let tag_options = TagOptions {
+        message: options.vcs.tag_message.as_deref(),
+        sign: options.vcs.sign_tag,
+    };
+    vcs.create_tag(path, tag, tag_options)?;
+    options.notify(&ReleaseEvent::Tagged { tag });
+    Ok(())
+}
+
+/// Pushes version change commit, creates release tag and pushes it.
+/// In atomic mode tag created first and then pushed together with branch
+fn push_release(vcs: &impl Vcs, path: &str, tag: &str, options: &ReleaseOptions) -> Result<()> {
+    let push_options = options.vcs.push_options();
+    let (remote, branch) = (push_options.remote, push_options.branch);
+    if options.vcs.atomic {
+        create_tag(vcs, path, tag, options)?;
+        vcs.push_atomic(path, tag, push_options)?;
+        options.notify(&ReleaseEvent::PushedAtomic {
+            remote,
+            branch,
+            tag,
+        });
+    } else {
+        vcs.push(path, options.vcs.push_options())?;
+        options.notify(&ReleaseEvent::Pushed { remote, branch });
+        create_tag(vcs, path, tag, options)?;
+        vcs.push_tag(path, tag, push_options)?;
+        options.notify(&ReleaseEvent::TagPushed { remote, tag });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_in_result)]
     #![allow(clippy::unwrap_used)]
     use super::*;
     use crate::MockVcs;
-    use crate::{CARGO_CONFIG, MockPublisher};
-    use mockall::predicate::{eq, str};
+    use crate::{CARGO_CONFIG, MockIndex, MockPublisher, NonIndex, PublishError};
+    use mockall::Sequence;
+    use mockall::predicate::eq;
     use rstest::{fixture, rstest};
     use vfs::MemoryFS;
 
@@ -174,18 +1075,25 @@ mod tests {
     fn release_workspace(root: VfsPath, #[case] all_features: bool) {
         // Arrange
         let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
         let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
 
         mock_vcs
             .expect_commit()
-            .with(eq("/x"), eq("changelog: v0.2.0"))
+            .withf(|p, o| {
+                p == "/x"
+                    && o.message == "changelog: v0.2.0"
+                    && o.files == ["solv/Cargo.toml", "solp/Cargo.toml"]
+                    && !o.sign
+            })
             .times(1)
             .returning(|_, _| Ok(()));
 
         let solp_options: PublishOptions = PublishOptions {
             crate_to_publish: Some("solp"),
             all_features,
-            no_verify: false,
+            ..Default::default()
         };
         mock_pub
             .expect_publish()
@@ -196,7 +1104,7 @@ mod tests {
         let solv_options: PublishOptions = PublishOptions {
             crate_to_publish: Some("solv"),
             all_features,
-            no_verify: false,
+            ..Default::default()
         };
         mock_pub
             .expect_publish()
@@ -206,27 +1114,34 @@ mod tests {
 
         mock_vcs
             .expect_push()
-            .with(eq("/x"))
+            .withf(|p, o| p == "/x" && *o == PushOptions::default())
             .times(1)
-            .returning(|_| Ok(()));
+            .returning(|_, _| Ok(()));
 
         mock_vcs
             .expect_create_tag()
-            .with(eq("/x"), eq("v0.2.0"))
+            .withf(|p, t, o| p == "/x" && t == "v0.2.0" && *o == TagOptions::default())
             .times(1)
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
 
         mock_vcs
             .expect_push_tag()
-            .with(eq("/x"), eq("v0.2.0"))
+            .withf(|p, t, o| p == "/x" && t == "v0.2.0" && *o == PushOptions::default())
             .times(1)
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
 
-        let w = Workspace::new(0, mock_pub, mock_vcs);
+        let w = Workspace::new(mock_pub, mock_vcs, not_published());
         let path = VPath::new("/x", root);
 
+        let options = ReleaseOptions::builder(Increment::Minor)
+            .publish(PublishSettings {
+                all_features,
+                ..Default::default()
+            })
+            .build();
+
         // Act
-        let r = w.release(path, Increment::Minor, all_features, false);
+        let r = w.release(path, &options);
 
         // Assert
         assert!(r.is_ok());
@@ -239,18 +1154,34 @@ mod tests {
     fn release_crate(root: VfsPath, #[case] all_features: bool) {
         // Arrange
         let mut mock_pub = MockPublisher::new();
+        let package_options = PublishOptions {
+            all_features,
+            allow_dirty: true,
+            ..Default::default()
+        };
+        mock_pub
+            .expect_package()
+            .withf(move |p, c, o| p == "/x" && c.is_empty() && *o == package_options)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
         let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
 
         mock_vcs
             .expect_commit()
-            .with(eq("/x"), eq("changelog: v0.2.0"))
+            .withf(|p, o| {
+                p == "/x"
+                    && o.message == "changelog: v0.2.0"
+                    && o.files == ["Cargo.toml"]
+                    && !o.sign
+            })
             .times(1)
             .returning(|_, _| Ok(()));
 
         let options: PublishOptions = PublishOptions {
             crate_to_publish: None,
             all_features,
-            no_verify: false,
+            ..Default::default()
         };
         mock_pub
             .expect_publish()
@@ -260,33 +1191,1035 @@ mod tests {
 
         mock_vcs
             .expect_push()
-            .with(eq("/x"))
+            .withf(|p, o| p == "/x" && *o == PushOptions::default())
             .times(1)
-            .returning(|_| Ok(()));
+            .returning(|_, _| Ok(()));
 
         mock_vcs
             .expect_create_tag()
-            .with(eq("/x"), eq("v0.2.0"))
+            .withf(|p, t, o| p == "/x" && t == "v0.2.0" && *o == TagOptions::default())
             .times(1)
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
 
         mock_vcs
             .expect_push_tag()
-            .with(eq("/x"), eq("v0.2.0"))
+            .withf(|p, t, o| p == "/x" && t == "v0.2.0" && *o == PushOptions::default())
             .times(1)
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
 
-        let c = Crate::new(mock_pub, mock_vcs);
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
 
         let path = VPath::new("/x", root.join("solp").unwrap());
 
+        let options = ReleaseOptions::builder(Increment::Minor)
+            .publish(PublishSettings {
+                all_features,
+                ..Default::default()
+            })
+            .build();
+
         // Act
-        let r = c.release(path, Increment::Minor, all_features, false);
+        let r = c.release(path, &options);
 
         // Assert
         assert!(r.is_ok());
     }
 
+    #[rstest]
+    fn release_crate_signed_annotated_tag(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_supports().returning(|_| true);
+
+        mock_vcs
+            .expect_commit()
+            .withf(|p, o| p == "/x" && o.message == "changelog: v0.1.14" && o.sign)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        mock_pub.expect_publish().times(1).returning(|_, _| Ok(()));
+
+        mock_vcs
+            .expect_push()
+            .withf(|p, o| p == "/x" && *o == PushOptions::default())
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let tag_options = TagOptions {
+            message: Some("release notes"),
+            sign: true,
+        };
+        mock_vcs
+            .expect_create_tag()
+            .withf(move |p, t, o| p == "/x" && t == "v0.1.14" && *o == tag_options)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        mock_vcs
+            .expect_push_tag()
+            .withf(|p, t, o| p == "/x" && t == "v0.1.14" && *o == PushOptions::default())
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
+
+        let path = VPath::new("/x", root.join("solp").unwrap());
+        let vcs_options = VcsOptions {
+            tag_message: Some("release notes".to_string()),
+            sign_tag: true,
+            sign_commit: true,
+            ..Default::default()
+        };
+
+        let options = ReleaseOptions::builder(Increment::Patch)
+            .vcs(vcs_options)
+            .build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_crate_atomic_push(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_supports().returning(|_| true);
+        let mut seq = Sequence::new();
+
+        mock_vcs
+            .expect_commit()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+
+        mock_pub
+            .expect_publish()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+
+        mock_vcs
+            .expect_create_tag()
+            .withf(|p, t, _| p == "/x" && t == "v1.0.0")
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+
+        let push_options = PushOptions {
+            remote: "upstream",
+            branch: Some("main"),
+        };
+        mock_vcs
+            .expect_push_atomic()
+            .withf(move |p, t, o| p == "/x" && t == "v1.0.0" && *o == push_options)
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+
+        mock_vcs.expect_push().never();
+        mock_vcs.expect_push_tag().never();
+
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
+
+        let path = VPath::new("/x", root.join("solp").unwrap());
+        let vcs_options = VcsOptions {
+            remote: "upstream".to_string(),
+            branch: Some("main".to_string()),
+            atomic: true,
+            ..Default::default()
+        };
+
+        let options = ReleaseOptions::builder(Increment::Major)
+            .vcs(vcs_options)
+            .build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_crate_commits_only_release_files(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+
+        mock_vcs
+            .expect_commit()
+            .withf(|p, o| p == "/x" && o.files == ["Cargo.toml", "Cargo.lock", "CHANGELOG.md"])
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_pub.expect_publish().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+
+        let crate_root = root.join("solp").unwrap();
+        for file in [CARGO_LOCK, "CHANGELOG.md"] {
+            crate_root
+                .join(file)
+                .unwrap()
+                .create_file()
+                .unwrap()
+                .write_all(b"x")
+                .unwrap();
+        }
+
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
+
+        let path = VPath::new("/x", crate_root);
+        let vcs_options = VcsOptions {
+            files: vec!["CHANGELOG.md".to_string(), "MISSING.md".to_string()],
+            ..Default::default()
+        };
+
+        let options = ReleaseOptions::builder(Increment::Patch)
+            .vcs(vcs_options)
+            .build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_crate_dirty_working_tree(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs
+            .expect_is_clean()
+            .with(eq("/x"))
+            .returning(|_| Ok(false));
+        mock_vcs.expect_commit().never();
+        mock_pub.expect_publish().never();
+
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
+
+        let crate_root = root.join("solp").unwrap();
+        let path = VPath::new("/x", crate_root.clone());
+
+        let options = ReleaseOptions::builder(Increment::Patch).build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(matches!(r, Err(Error::Vcs { .. })));
+        let conf = CrateConfig::open(&crate_root.join(CARGO_CONFIG).unwrap()).unwrap();
+        assert_eq!("0.1.13", conf.package.version);
+    }
+
+    #[rstest]
+    fn release_crate_dirty_working_tree_allowed(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().never();
+        mock_vcs.expect_commit().times(1).returning(|_, _| Ok(()));
+        mock_pub.expect_publish().times(1).returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
+
+        let path = VPath::new("/x", root.join("solp").unwrap());
+        let vcs_options = VcsOptions {
+            allow_dirty_tree: true,
+            ..Default::default()
+        };
+
+        let options = ReleaseOptions::builder(Increment::Patch)
+            .vcs(vcs_options)
+            .build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_crate_unsupported_vcs_feature(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().never();
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs
+            .expect_supports()
+            .with(eq(VcsFeature::SignTag))
+            .returning(|_| false);
+        mock_vcs.expect_is_clean().never();
+        mock_vcs.expect_commit().never();
+        mock_pub.expect_publish().never();
+
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
+
+        let crate_root = root.join("solp").unwrap();
+        let path = VPath::new("/x", crate_root.clone());
+        let vcs_options = VcsOptions {
+            sign_tag: true,
+            ..Default::default()
+        };
+
+        let options = ReleaseOptions::builder(Increment::Patch)
+            .vcs(vcs_options)
+            .build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(matches!(r, Err(Error::Vcs { .. })));
+        let conf = CrateConfig::open(&crate_root.join(CARGO_CONFIG).unwrap()).unwrap();
+        assert_eq!("0.1.13", conf.package.version);
+    }
+
+    #[rstest]
+    fn release_workspace_to_registry_skips_not_allowed(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        let mut mock_index = MockIndex::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_commit().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+
+        let solp = root.join("solp").unwrap().join(CARGO_CONFIG).unwrap();
+        solp.create_file()
+            .unwrap()
+            .write_all(
+                SOLP.replace(
+                    "[build-dependencies]",
+                    "publish = [\"internal\"]\n\n[build-dependencies]",
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        let solv = root.join("solv").unwrap().join(CARGO_CONFIG).unwrap();
+        solv.create_file()
+            .unwrap()
+            .write_all(
+                SOLV.replace("[dependencies]", "publish = false\n\n[dependencies]")
+                    .as_bytes(),
+            )
+            .unwrap();
+
+        mock_pub
+            .expect_publish()
+            .withf(|p, o| {
+                p == "/x" && o.crate_to_publish == Some("solp") && o.registry == Some("internal")
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        // the only crate published so there is no need to wait for it in index
+        mock_index
+            .expect_contains()
+            .withf(|r, n, v| *r == Some("internal") && n == "solp" && v == "0.2.0")
+            .times(1)
+            .returning(|_, _, _| Ok(false));
+
+        let w = Workspace::new(mock_pub, mock_vcs, mock_index);
+        let path = VPath::new("/x", root);
+
+        let options = ReleaseOptions::builder(Increment::Minor)
+            .publish(PublishSettings {
+                registry: Some("internal".to_string()),
+                ..Default::default()
+            })
+            .delay_seconds(1)
+            .build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_workspace_member_publish_settings(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_commit().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+
+        let wks = format!(
+            "{WKS}\n[workspace.metadata.releaser.members.solv]\nfeatures = [\"x\"]\nlocked = false\njobs = 2\n"
+        );
+        root.join(CARGO_CONFIG)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(wks.as_bytes())
+            .unwrap();
+
+        mock_pub
+            .expect_publish()
+            .withf(|_, o| {
+                o.crate_to_publish == Some("solp")
+                    && o.features.is_empty()
+                    && o.target == Some("wasm32-wasip1")
+                    && o.locked
+                    && o.jobs.is_none()
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_pub
+            .expect_publish()
+            .withf(|_, o| {
+                o.crate_to_publish == Some("solv")
+                    && o.features == ["x"]
+                    && o.target == Some("wasm32-wasip1")
+                    && !o.locked
+                    && o.jobs == Some(2)
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let w = Workspace::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root);
+        let publish = PublishSettings {
+            target: Some("wasm32-wasip1".to_string()),
+            locked: true,
+            ..Default::default()
+        };
+
+        let options = ReleaseOptions::builder(Increment::Minor)
+            .publish(publish)
+            .build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_workspace_dry_run(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().times(1).returning(|_| Ok(true));
+        let w = Workspace::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root.clone());
+        let hooks = Hooks {
+            before_commit: vec!["exit 1".to_string()],
+            after_release: vec!["exit 1".to_string()],
+        };
+        let options = ReleaseOptions::builder(Increment::Minor)
+            .delay_seconds(20)
+            .dry_run(true)
+            .hooks(hooks)
+            .build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+        let conf = CrateConfig::open(&root.join("solp/Cargo.toml").unwrap()).unwrap();
+        assert_eq!("0.1.13", conf.package.version);
+    }
+
+    #[cfg(unix)]
+    #[rstest]
+    fn release_crate_before_commit_hook_failed(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_commit().never();
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = VPath::new(dir.path().to_str().unwrap(), root.join("solp").unwrap());
+        let hooks = Hooks {
+            before_commit: vec![
+                "echo $RELEASER_VERSION > version.txt".to_string(),
+                "exit 1".to_string(),
+            ],
+            ..Default::default()
+        };
+        let options = ReleaseOptions::builder(Increment::Patch)
+            .hooks(hooks)
+            .build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(r.is_err());
+        let version = std::fs::read_to_string(dir.path().join("version.txt")).unwrap();
+        assert_eq!("0.1.14", version.trim());
+        let manifest = root.join("solp").unwrap().join(CARGO_CONFIG).unwrap();
+        let conf = CrateConfig::open(&manifest).unwrap();
+        assert_eq!("0.1.13", conf.package.version);
+    }
+
+    #[rstest]
+    fn release_workspace_commit_failed(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        mock_vcs
+            .expect_commit()
+            .times(1)
+            .returning(|_, _| Err(Error::vcs("commit failed")));
+        mock_pub.expect_publish().never();
+        let w = Workspace::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root.clone());
+        let options = ReleaseOptions::builder(Increment::Minor).build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(matches!(r, Err(Error::Vcs { .. })));
+        for member in ["solv", "solp"] {
+            let manifest = root.join(member).unwrap().join(CARGO_CONFIG).unwrap();
+            let conf = CrateConfig::open(&manifest).unwrap();
+            assert_eq!("0.1.13", conf.package.version);
+        }
+    }
+
+    #[rstest]
+    fn release_crate_publish_retried_on_transient_error(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_commit().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+        let mut seq = Sequence::new();
+        mock_pub
+            .expect_publish()
+            .times(2)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Err(PublishError::Transient("status 429".to_string()).into()));
+        mock_pub
+            .expect_publish()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root.join("solp").unwrap());
+        let options = ReleaseOptions::builder(Increment::Patch)
+            .retry(RetrySettings {
+                retries: 2,
+                backoff_seconds: 0,
+            })
+            .build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_crate_transient_error_after_upload_succeeded(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_commit().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+        mock_pub
+            .expect_publish()
+            .times(1)
+            .returning(|_, _| Err(PublishError::Transient("got 503".to_string()).into()));
+        let mut mock_index = MockIndex::new();
+        let mut seq = Sequence::new();
+        mock_index
+            .expect_contains()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(false));
+        mock_index
+            .expect_contains()
+            .withf(|r, n, v| r.is_none() && n == "solp" && v == "0.1.14")
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(true));
+        let c = Crate::new(mock_pub, mock_vcs, mock_index);
+        let path = VPath::new("/x", root.join("solp").unwrap());
+        let options = ReleaseOptions::builder(Increment::Patch)
+            .retry(RetrySettings {
+                retries: 2,
+                backoff_seconds: 0,
+            })
+            .build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    #[case::permanent(false, 1)]
+    #[case::retries_exhausted(true, 3)]
+    #[trace]
+    fn release_crate_publish_failed(
+        root: VfsPath,
+        #[case] transient: bool,
+        #[case] attempts: usize,
+    ) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_commit().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().never();
+        mock_pub
+            .expect_publish()
+            .times(attempts)
+            .returning(move |_, _| {
+                let message = "failed".to_string();
+                if transient {
+                    Err(PublishError::Transient(message).into())
+                } else {
+                    Err(PublishError::Permanent(message).into())
+                }
+            });
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root.join("solp").unwrap());
+        let options = ReleaseOptions::builder(Increment::Patch)
+            .retry(RetrySettings {
+                retries: 2,
+                backoff_seconds: 0,
+            })
+            .build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(matches!(r, Err(Error::Publish(e)) if e.is_transient() == transient));
+    }
+
+    #[rstest]
+    fn release_workspace_skips_already_published(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        let mut mock_index = MockIndex::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_commit().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+        mock_index
+            .expect_contains()
+            .withf(|_, n, _| n == "solp")
+            .returning(|_, _, _| Ok(true));
+        mock_index
+            .expect_contains()
+            .withf(|_, n, _| n == "solv")
+            .returning(|_, _, _| Err(Error::registry("index unavailable")));
+        mock_pub
+            .expect_publish()
+            .withf(|_, o| o.crate_to_publish == Some("solv"))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let w = Workspace::new(mock_pub, mock_vcs, mock_index);
+        let path = VPath::new("/x", root);
+        let options = ReleaseOptions::builder(Increment::Minor).build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_crate_skips_already_published(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        let mut mock_index = MockIndex::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_commit().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+        mock_index
+            .expect_contains()
+            .withf(|r, n, v| r.is_none() && n == "solp" && v == "0.1.14")
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+        mock_pub.expect_publish().never();
+        let c = Crate::new(mock_pub, mock_vcs, mock_index);
+        let path = VPath::new("/x", root.join("solp").unwrap());
+        let options = ReleaseOptions::builder(Increment::Patch).build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_workspace_packages_all_crates_before_commit(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        let mut mock_vcs = MockVcs::new();
+        let mut seq = Sequence::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_pub
+            .expect_package()
+            .withf(|p, c, o| {
+                p == "/x" && c == ["solp", "solv"] && o.crate_to_publish.is_none() && o.allow_dirty
+            })
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+        mock_vcs
+            .expect_commit()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+        mock_pub
+            .expect_publish()
+            .times(2)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+        let w = Workspace::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root);
+        let options = ReleaseOptions::builder(Increment::Minor).build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_workspace_packages_with_member_settings(root: VfsPath) {
+        // Arrange
+        let wks = format!(
+            "{WKS}\n[workspace.metadata.releaser.members.solv]\nfeatures = [\"x\"]\nallow-dirty = false\n"
+        );
+        root.join(CARGO_CONFIG)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(wks.as_bytes())
+            .unwrap();
+        let mut mock_pub = MockPublisher::new();
+        let mut mock_vcs = MockVcs::new();
+        let mut seq = Sequence::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_pub
+            .expect_package()
+            .withf(|_, c, o| c == ["solp", "solv"] && o.features == ["solv/x"] && o.allow_dirty)
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(()));
+        mock_vcs.expect_commit().returning(|_, _| Ok(()));
+        mock_pub.expect_publish().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+        let w = Workspace::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root);
+        let options = ReleaseOptions::builder(Increment::Minor).build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_workspace_packaging_member_target_differs(root: VfsPath) {
+        // Arrange
+        let wks = format!("{WKS}\n[workspace.metadata.releaser.members.solv]\ntarget = \"x\"\n");
+        root.join(CARGO_CONFIG)
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(wks.as_bytes())
+            .unwrap();
+        let mut mock_pub = MockPublisher::new();
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_pub.expect_package().never();
+        mock_vcs.expect_commit().never();
+        mock_pub.expect_publish().never();
+        let w = Workspace::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root.clone());
+        let options = ReleaseOptions::builder(Increment::Minor).build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(matches!(r, Err(Error::Packaging { .. })));
+        for member in ["solv", "solp"] {
+            let manifest = root.join(member).unwrap().join(CARGO_CONFIG).unwrap();
+            let conf = CrateConfig::open(&manifest).unwrap();
+            assert_eq!("0.1.13", conf.package.version);
+        }
+    }
+
+    #[rstest]
+    fn release_workspace_packaging_failed(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_pub.expect_package().times(1).returning(|_, _, _| {
+            Err(PublishError::Permanent("failed to verify".to_string()).into())
+        });
+        mock_vcs.expect_commit().never();
+        mock_pub.expect_publish().never();
+        let w = Workspace::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root.clone());
+        let observer = Arc::new(RecordingObserver::default());
+        let options = ReleaseOptions::builder(Increment::Minor)
+            .observer(observer.clone())
+            .build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(matches!(r, Err(Error::Packaging { .. })));
+        for member in ["solv", "solp"] {
+            let manifest = root.join(member).unwrap().join(CARGO_CONFIG).unwrap();
+            let conf = CrateConfig::open(&manifest).unwrap();
+            assert_ne!("0.2.0", conf.package.version);
+        }
+        let events = observer.events.lock().unwrap();
+        assert_eq!(
+            ["reverted", "failed Packaging verification failed"],
+            events[events.len() - 2..]
+        );
+    }
+
+    #[rstest]
+    fn release_workspace_dependency_cycle(root: VfsPath) {
+        // Arrange
+        add_solp_dependency(&root, "dependencies");
+        let mut mock_pub = MockPublisher::new();
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_pub.expect_package().never();
+        mock_vcs.expect_commit().never();
+        mock_pub.expect_publish().never();
+        let w = Workspace::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root.clone());
+        let options = ReleaseOptions::builder(Increment::Minor).build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(matches!(r, Err(Error::DependencyCycle(ref c)) if c == &["solv", "solp"]));
+        for member in ["solv", "solp"] {
+            let manifest = root.join(member).unwrap().join(CARGO_CONFIG).unwrap();
+            let conf = CrateConfig::open(&manifest).unwrap();
+            assert_eq!("0.1.13", conf.package.version);
+        }
+    }
+
+    #[rstest]
+    fn release_workspace_dev_dependency_cycle_allowed(root: VfsPath) {
+        // Arrange
+        add_solp_dependency(&root, "dev-dependencies");
+        let mut mock_pub = MockPublisher::new();
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_commit().times(1).returning(|_, _| Ok(()));
+        mock_pub.expect_publish().times(2).returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+        let w = Workspace::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root);
+        let options = ReleaseOptions::builder(Increment::Minor).build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+    }
+
+    #[rstest]
+    fn release_crate_events(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        mock_pub.expect_publish().returning(|_, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_commit().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs
+            .expect_push_tag()
+            .returning(|_, _, _| Err(Error::vcs("rejected")));
+        let c = Crate::new(mock_pub, mock_vcs, not_published());
+        let path = VPath::new("/x", root.join("solp").unwrap());
+        let observer = Arc::new(RecordingObserver::default());
+        let options = ReleaseOptions::builder(Increment::Patch)
+            .observer(observer.clone())
+            .build();
+
+        // Act
+        let r = c.release(path, &options);
+
+        // Assert
+        assert!(matches!(r, Err(Error::Vcs { .. })));
+        let events = observer.events.lock().unwrap();
+        assert_eq!(
+            vec![
+                "version 0.1.14",
+                "rewritten Cargo.toml",
+                "packaging ",
+                "committed changelog: v0.1.14",
+                "publishing solp",
+                "published solp",
+                "pushed",
+                "tagged v0.1.14",
+                "failed rejected",
+            ],
+            *events
+        );
+    }
+
+    #[rstest]
+    fn release_workspace_non_index_no_waiting(root: VfsPath) {
+        // Arrange
+        let mut mock_pub = MockPublisher::new();
+        mock_pub.expect_package().returning(|_, _, _| Ok(()));
+        mock_pub.expect_publish().times(2).returning(|_, _| Ok(()));
+        let mut mock_vcs = MockVcs::new();
+        mock_vcs.expect_is_clean().returning(|_| Ok(true));
+        mock_vcs.expect_commit().returning(|_, _| Ok(()));
+        mock_vcs.expect_push().returning(|_, _| Ok(()));
+        mock_vcs.expect_create_tag().returning(|_, _, _| Ok(()));
+        mock_vcs.expect_push_tag().returning(|_, _, _| Ok(()));
+        let w = Workspace::new(mock_pub, mock_vcs, NonIndex);
+        let path = VPath::new("/x", root);
+        let observer = Arc::new(RecordingObserver::default());
+        let options = ReleaseOptions::builder(Increment::Minor)
+            .delay_seconds(20)
+            .observer(observer.clone())
+            .build();
+
+        // Act
+        let r = w.release(path, &options);
+
+        // Assert
+        assert!(r.is_ok());
+        let events = observer.events.lock().unwrap();
+        assert!(
+            !events.iter().any(|e| e.starts_with("Waiting")),
+            "{events:?}"
+        );
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl ReleaseObserver for RecordingObserver {
+        fn on_event(&self, event: &ReleaseEvent<'_>, _dry_run: bool) {
+            let event = match event {
+                ReleaseEvent::VersionComputed { version } => format!("version {version}"),
+                ReleaseEvent::FileRewritten { path } => format!("rewritten {path}"),
+                ReleaseEvent::Packaging { crates } => format!("packaging {}", crates.join(",")),
+                ReleaseEvent::Reverted => "reverted".to_string(),
+                ReleaseEvent::Committed { message, .. } => format!("committed {message}"),
+                ReleaseEvent::Publishing { name, .. } => format!("publishing {name}"),
+                ReleaseEvent::Published { name, .. } => format!("published {name}"),
+                ReleaseEvent::Pushed { .. } => "pushed".to_string(),
+                ReleaseEvent::Tagged { tag } => format!("tagged {tag}"),
+                ReleaseEvent::Failed { error } => format!("failed {error}"),
+                e => format!("{e:?}"),
+            };
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    fn not_published() -> MockIndex {
+        let mut mock_index = MockIndex::new();
+        mock_index.expect_contains().returning(|_, _, _| Ok(false));
+        mock_index
+    }
+
+    /// Makes solp depend on solv which depends on solp itself
+    fn add_solp_dependency(root: &VfsPath, section: &str) {
+        let manifest = root.join("solp").unwrap().join(CARGO_CONFIG).unwrap();
+        let table = format!("[{section}]\n");
+        let dependency = "solv = { path = \"../solv/\", version = \"0.1.13\" }\n";
+        let content = if SOLP.contains(&table) {
+            SOLP.replace(&table, &format!("{table}{dependency}"))
+        } else {
+            format!("{SOLP}\n{table}{dependency}")
+        };
+        manifest
+            .create_file()
+            .unwrap()
+            .write_all(content.as_bytes())
+            .unwrap();
+    }
+
     #[fixture]
     fn root() -> VfsPath {
         let root = VfsPath::new(MemoryFS::new());